@@ -0,0 +1,35 @@
+// Cost of one physics step across scene sizes: `cargo bench`, or
+// `cargo bench --features parallel` for the threaded path
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+
+// The game is a single binary, so the bench builds its source as a module
+#[allow(dead_code)]
+#[path = "../src/main.rs"]
+mod game;
+
+use game::{PhysicsApp, SOLVER_DT};
+
+fn step(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step");
+    for count in [10, 100, 1000] {
+        for dense in [true, false] {
+            let mut app = PhysicsApp::benchmark_scene(count, dense);
+            let scene = if dense { "dense" } else { "sparse" };
+            group.bench_function(BenchmarkId::new(scene, count), |b| b.iter(|| app.step(SOLVER_DT)));
+        }
+    }
+    group.finish();
+
+    // One dense scene stepped both ways, the threaded side only differs with `--features parallel`
+    let mut group = c.benchmark_group("parallel_step");
+    for parallel in [false, true] {
+        let mut app = PhysicsApp::benchmark_scene(500, true);
+        app.parallel_step = parallel;
+        let mode = if parallel { "threaded" } else { "serial" };
+        group.bench_function(BenchmarkId::new(mode, 500), |b| b.iter(|| app.step(SOLVER_DT)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, step);
+criterion_main!(benches);
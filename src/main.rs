@@ -1,7 +1,67 @@
 use eframe::egui;
-use std::time::Instant;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const SAVEGAME_PATH: &str = "savegame.json";
+const TRAJECTORY_PATH: &str = "trajectory.csv";
+const SVG_PATH: &str = "level.svg";
+
+// Hand-made levels in play order, level 1 first. Adding a level is one entry here;
+// anything past the end is endless mode territory
+const LEVELS: &[fn(&mut PhysicsApp)] = &[
+    PhysicsApp::setup_level_1,
+    PhysicsApp::setup_level_2,
+    PhysicsApp::setup_level_3,
+    PhysicsApp::setup_level_4,
+    PhysicsApp::setup_level_5,
+];
+const LEVEL_COUNT: u32 = LEVELS.len() as u32;
+
+// Left edge of the play area; the side panel sits to its left
+const BOARDER_START: f32 = 210.0;
+// Size the levels were designed for, kept fixed when scaling to fit the window
+const DESIGN_BOUNDS: (f32, f32) = (800.0, 600.0);
+
+// Headless solver: fixed step, time limit per attempt and number of random layouts tried
+pub(crate) const SOLVER_DT: f32 = 1.0 / 60.0;
+const SOLVER_MAX_TIME: f32 = 8.0;
+const SOLVER_ATTEMPTS: usize = 60;
+// Longest frame the live sim catches up on, a stall past this is dropped instead of replayed
+const MAX_FRAME_TIME: f32 = 0.1;
+
+// Distinct solutions the Hint button searches for and cycles through
+const HINT_SOLUTIONS: usize = 3;
+
+// Generated levels are rerolled until the solver beats them, up to this many times or
+// this many real seconds, since it runs inside a frame
+const GENERATOR_TRIES: usize = 20;
+const GENERATOR_TIME_LIMIT: f32 = 0.5;
+
+// Seconds a first click on Restart Level or Clear User Walls waits for the confirming one
+const CONFIRM_WINDOW: f32 = 2.0;
+
+// Seconds for each half of a level change: fade to black, then back in on the new level
+const TRANSITION_TIME: f32 = 0.3;
+
+// How far back the rewind history reaches, in simulated seconds
+const REWIND_SECONDS: f32 = 10.0;
 
 fn main() -> Result<(), eframe::Error> {
+    // Print every hit to stdout, e.g. to tune sounds against: `cargo run -- --log-collisions`
+    let log_collisions = std::env::args().any(|arg| arg == "--log-collisions");
+    // Headless check that every shipped level still falls to its reference solution
+    if std::env::args().any(|arg| arg == "--verify-levels") {
+        if !verify_levels() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1000.0, 600.0])
@@ -12,10 +72,99 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Physics Puzzle Game",
         options,
-        Box::new(|_cc| Ok(Box::new(PhysicsApp::default()))),
+        Box::new(|cc| {
+            let ball_texture = cc.egui_ctx.load_texture("ball", ball_texture_image(), egui::TextureOptions::LINEAR);
+            let collision_observer: Option<Rc<RefCell<dyn CollisionObserver>>> = if log_collisions {
+                Some(Rc::new(RefCell::new(LogObserver)))
+            } else {
+                None
+            };
+            Ok(Box::new(PhysicsApp { ball_texture: Some(ball_texture), collision_observer, ..Default::default() }))
+        }),
     )
 }
 
+// Two-step button guard: while `needed`, the first click only arms it and a second click
+// within CONFIRM_WINDOW goes through
+fn confirm_click(needed: bool, armed_at: &mut Option<Instant>) -> bool {
+    if needed && !is_armed(*armed_at) {
+        *armed_at = Some(Instant::now());
+        return false;
+    }
+    *armed_at = None;
+    true
+}
+
+fn is_armed(armed_at: Option<Instant>) -> bool {
+    armed_at.is_some_and(|t| t.elapsed().as_secs_f32() <= CONFIRM_WINDOW)
+}
+
+// A wall as collision sees it: a thick segment with round caps at both ends
+fn draw_capsule(painter: &egui::Painter, start: Vec2, end: Vec2, thickness: f32, color: egui::Color32) {
+    let (start, end) = (egui::pos2(start.x, start.y), egui::pos2(end.x, end.y));
+    painter.line_segment([start, end], egui::Stroke::new(thickness, color));
+    painter.circle_filled(start, thickness / 2.0, color);
+    painter.circle_filled(end, thickness / 2.0, color);
+}
+
+// Zigzag points for a spring drawn from `from` to `to`
+fn spring_zigzag(from: Vec2, to: Vec2) -> Vec<Vec2> {
+    let dist = from.distance(&to);
+    let segments = (dist / 10.0).max(4.0) as i32;
+    let step = (to - from) / segments as f32;
+    (0..=segments).map(|i| {
+        let offset = if i % 2 == 0 { 5.0 } else { -5.0 };
+        from + step * i as f32 + step.perp() * (offset / dist)
+    }).collect()
+}
+
+// Spokes of the star marking a goal: tip to inner point, five times round
+fn goal_star(center: Vec2) -> [(Vec2, Vec2); 5] {
+    let star_size = 15.0;
+    std::array::from_fn(|i| {
+        // First tip points straight up, the inner point sits half a spoke further round
+        let tip = Vec2::new(0.0, -star_size).rotate(std::f32::consts::TAU * i as f32 / 5.0);
+        let inner = tip.rotate(std::f32::consts::PI / 5.0) * 0.5;
+        (center + tip, center + inner)
+    })
+}
+
+// Procedural beach ball with coloured panels, so the sprite's spin is easy to see
+fn ball_texture_image() -> egui::ColorImage {
+    const SIZE: usize = 64;
+    const PANELS: [egui::Color32; 3] = [egui::Color32::WHITE, egui::Color32::RED, egui::Color32::from_rgb(40, 90, 220)];
+    let center = SIZE as f32 / 2.0;
+    let pixels = (0..SIZE * SIZE).map(|i| {
+        let (x, y) = ((i % SIZE) as f32 + 0.5 - center, (i / SIZE) as f32 + 0.5 - center);
+        if x * x + y * y > center * center {
+            return egui::Color32::TRANSPARENT;
+        }
+        let angle = y.atan2(x).rem_euclid(std::f32::consts::TAU);
+        let panel = (angle / std::f32::consts::TAU * 6.0) as usize % PANELS.len();
+        PANELS[panel]
+    }).collect();
+    egui::ColorImage::new([SIZE, SIZE], pixels)
+}
+
+// Replays each level's reference solution and reports which still win
+fn verify_levels() -> bool {
+    let mut all_pass = true;
+    for level in 1..=LEVEL_COUNT {
+        let mut app = PhysicsApp { level, ..Default::default() };
+        app.setup_level(level);
+        let result = match &app.reference_solution {
+            Some(replay) if app.replay_wins(replay) => "ok",
+            Some(_) => {
+                all_pass = false;
+                "FAILED"
+            }
+            None => "no reference solution",
+        };
+        println!("level {level}: {result}");
+    }
+    all_pass
+}
+
 #[derive(Clone)]
 struct PhysicsObject {
     pos: Vec2,
@@ -30,9 +179,220 @@ struct PhysicsObject {
     fixed: bool,
     initial_pos: Vec2,
     initial_vel: Vec2,
+    repulsive: bool, // Pushes other balls away instead of being pushed
+    planet: bool, // Attracts loose balls in orbital mode
+    plasticity: f32, // Bounciness lost per hard hit (0 = perfectly elastic material)
+    initial_bounciness: f32,
+    lost: bool, // Fell out of the world; ignored until the level is reset
+    sleeping: bool, // Settled; skipped by integration and collisions until woken
+    sleep_timer: f32, // How long the object has been nearly still
+    sleep_anchor: Vec2, // Where the current still spell began, the object has to stay near it
+    gravity_flipped: bool, // Toggled by flip zones, gravity pulls the other way while set
+    angle: f32, // radians, clockwise on screen
+    angular_vel: f32,
+    texture: Option<egui::TextureHandle>, // Drawn as a spinning sprite instead of a flat circle
+    kinematic: Option<KinematicPath>, // Scripted motion, only meaningful on fixed objects
+    debug_acc: Vec2, // Acceleration of the last step, kept for the debug overlay since `acc` is cleared
+    health: Option<f32>, // Breakable: total hit impulse it takes before shattering
+    damage: f32, // Hit impulse taken so far this attempt
+    last_touched_by: Option<usize>, // Player ball whose momentum last reached this one, None for the environment
+    spawn_id: Option<u64>, // Order a sandbox ball was dropped in, None for the level's own objects
 }
 
-#[derive(Clone, Copy)]
+// Scripted motion for a fixed object: it loops from its initial position through the
+// waypoints and back at a constant speed. Forces don't move it, but it still shoves
+// dynamic balls like an infinitely heavy body
+#[derive(Clone, Serialize, Deserialize)]
+struct KinematicPath {
+    waypoints: Vec<Vec2>,
+    speed: f32, // px/s along the path
+}
+
+impl KinematicPath {
+    fn position_at(&self, start: Vec2, time: f32) -> Vec2 {
+        let points: Vec<Vec2> = std::iter::once(start).chain(self.waypoints.iter().copied()).collect();
+        let segments = || (0..points.len()).map(|i| (points[i], points[(i + 1) % points.len()]));
+        let total: f32 = segments().map(|(a, b)| a.distance(&b)).sum();
+        if total <= 0.0 {
+            return start;
+        }
+        let mut along = (time * self.speed).rem_euclid(total);
+        for (a, b) in segments() {
+            let length = a.distance(&b);
+            if along <= length && length > 0.0 {
+                return a + (b - a) * (along / length);
+            }
+            along -= length;
+        }
+        start
+    }
+}
+
+// Pairs further apart than touching plus this margin are skipped by the narrow phase
+const CONTACT_MARGIN: f32 = 4.0;
+
+// Islands with more than one contact (stacks, piles) get this many solver passes by default
+const STACK_SOLVER_PASSES: usize = 4;
+
+// Bundles of solver settings for people who don't want to tune each knob
+#[derive(Clone, Copy, PartialEq)]
+enum Preset {
+    // One pass everywhere and an eager rest threshold: cheapest, stacks may sag
+    Fast,
+    // The default tuning
+    Balanced,
+    // Extra substeps and iterations, balls only rest when really slow
+    Accurate,
+}
+
+impl Preset {
+    fn name(self) -> &'static str {
+        match self {
+            Preset::Fast => "Fast",
+            Preset::Balanced => "Balanced",
+            Preset::Accurate => "Accurate",
+        }
+    }
+}
+
+// Whether `hitter` touching `goal` counts toward the win: only free intermediate balls
+// do, never the player, a fixed ball or another goal
+fn is_goal_hit(goal: &PhysicsObject, hitter: &PhysicsObject) -> bool {
+    goal.is_goal && !hitter.is_goal && !hitter.is_player && !hitter.fixed
+}
+
+fn is_contact_candidate(a: &PhysicsObject, b: &PhysicsObject) -> bool {
+    if a.lost || b.lost || (a.sleeping && b.sleeping) || (a.fixed && b.fixed) {
+        return false;
+    }
+    a.circle().penetration(&b.circle()) > -CONTACT_MARGIN
+}
+
+// Restitution to use for a hit at `speed`: a rebound slower than `floor` is
+// dropped to zero so balls settle instead of micro-bouncing forever
+fn floored_restitution(speed: f32, restitution: f32, floor: f32) -> f32 {
+    if speed.abs() * restitution < floor { 0.0 } else { restitution }
+}
+
+// Runs `f` on every object, spread across threads when the parallel feature is on
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+fn for_each_object<F>(objects: &mut [PhysicsObject], parallel: bool, f: F)
+where
+    F: Fn(usize, &mut PhysicsObject) + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    if parallel {
+        use rayon::prelude::*;
+        objects.par_iter_mut().enumerate().for_each(|(i, obj)| f(i, obj));
+        return;
+    }
+    objects.iter_mut().enumerate().for_each(|(i, obj)| f(i, obj));
+}
+
+impl PhysicsObject {
+    // Plain dynamic ball at rest whose mass follows its size, density * πr²
+    fn ball(pos: Vec2, radius: f32, density: f32) -> Self {
+        PhysicsObject {
+            pos,
+            vel: Vec2::new(0.0, 0.0),
+            acc: Vec2::new(0.0, 0.0),
+            radius,
+            mass: density * std::f32::consts::PI * radius * radius,
+            color: egui::Color32::from_rgb(200, 200, 200),
+            bounciness: 0.8,
+            is_goal: false,
+            is_player: false,
+            fixed: false,
+            initial_pos: pos,
+            initial_vel: Vec2::new(0.0, 0.0),
+            repulsive: false,
+            planet: false,
+            plasticity: 0.0,
+            initial_bounciness: 0.8,
+            lost: false,
+            sleeping: false,
+            sleep_timer: 0.0,
+            sleep_anchor: pos,
+            gravity_flipped: false,
+            angle: 0.0,
+            angular_vel: 0.0,
+            texture: None,
+            kinematic: None,
+            debug_acc: Vec2::new(0.0, 0.0),
+            health: None,
+            damage: 0.0,
+            last_touched_by: None,
+            spawn_id: None,
+        }
+    }
+
+    // A hard hit permanently deadens a plastic ball
+    fn dent(&mut self) {
+        self.bounciness = (self.bounciness - self.plasticity).max(0.0);
+    }
+
+    fn circle(&self) -> Circle {
+        Circle { center: self.pos, radius: self.radius }
+    }
+
+    // How easily pushes move this object. Immovable ones and zero, negative or non-finite
+    // masses give 0 instead of dividing by them; repulsive ones use a stand-in mass
+    fn inv_mass(&self) -> f32 {
+        if self.fixed || self.kinematic.is_some() {
+            0.0
+        } else if self.repulsive {
+            REPULSIVE_INV_MASS
+        } else if self.mass > 0.0 && self.mass.is_finite() {
+            1.0 / self.mass
+        } else {
+            0.0
+        }
+    }
+
+    fn wake(&mut self) {
+        self.sleeping = false;
+        self.sleep_timer = 0.0;
+        self.sleep_anchor = self.pos;
+    }
+
+    // Friction at a surface contact spins the ball toward rolling without slipping
+    // (omega = v_tangential / r), limited by how hard it was pressed into the surface.
+    // `surface_normal` points from the surface toward the ball.
+    fn roll_against(&mut self, surface_normal: Vec2, normal_impulse: f32) {
+        // Speed along the surface tangent, surface_normal.perp()
+        let target = surface_normal.cross(&self.vel) / self.radius;
+        // Spin change per unit of friction impulse for a solid disc, I = m r² / 2
+        let max_change = WALL_FRICTION * normal_impulse * 2.0 / (self.mass * self.radius);
+        let change = (target - self.angular_vel).clamp(-max_change, max_change);
+        self.angular_vel = (self.angular_vel + change).clamp(-MAX_ANGULAR_VEL, MAX_ANGULAR_VEL);
+    }
+}
+
+// Surface grip that spins balls up to rolling, and a cap on spin
+const WALL_FRICTION: f32 = 0.4;
+const MAX_ANGULAR_VEL: f32 = 60.0;
+// Grip of kinematic movers, so a ball riding a moving platform is carried along
+const PLATFORM_FRICTION: f32 = 0.8;
+
+// Objects slower than this for SLEEP_TIME seconds, and still within SLEEP_DRIFT (px) of
+// where that began, fall asleep
+const SLEEP_SPEED: f32 = 15.0;
+const SLEEP_TIME: f32 = 0.5;
+const SLEEP_DRIFT: f32 = 2.0;
+// Non-gravity acceleration (springs, repulsion, planets) that wakes a sleeper
+const WAKE_ACCEL: f32 = 50.0;
+// An attempt where everything has stayed below SLEEP_SPEED for SLEEP_TIME is over,
+// checked only after this much sim time so a slow start isn't cut short
+const REST_FAIL_MIN_TIME: f32 = 1.0;
+
+// Largest start offset (px) and launch velocity change (px/s) per axis from start jitter
+const JITTER_POSITION: f32 = 4.0;
+const JITTER_VELOCITY: f32 = 10.0;
+const JITTER_TRIES: usize = 5;
+// Real seconds the jitter rerolls may take, they run inside the frame that starts the level
+const JITTER_TIME_LIMIT: f32 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 struct Vec2 {
     x: f32,
     y: f32,
@@ -43,8 +403,23 @@ impl Vec2 {
         Self { x, y }
     }
 
+    // Unit vector at `radians` from the +x axis, clockwise on screen since y points down
+    fn from_angle(radians: f32) -> Vec2 {
+        Vec2::new(radians.cos(), radians.sin())
+    }
+
+    fn rotate(&self, radians: f32) -> Vec2 {
+        let (sin, cos) = radians.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
     fn length(&self) -> f32 {
-        (self.x * self.x + self.y * self.y).sqrt()
+        self.length_squared().sqrt()
+    }
+
+    // Cheap for comparisons against a squared threshold
+    fn length_squared(&self) -> f32 {
+        self.x * self.x + self.y * self.y
     }
 
     fn normalized(&self) -> Self {
@@ -56,9 +431,46 @@ impl Vec2 {
         }
     }
 
+    // Scaled down to `max` if longer, shorter vectors are returned as is
+    fn clamp_length(&self, max: f32) -> Vec2 {
+        let len_sq = self.length_squared();
+        if len_sq > max * max {
+            *self * (max / len_sq.sqrt())
+        } else {
+            *self
+        }
+    }
+
+    fn distance(&self, other: &Vec2) -> f32 {
+        (*self - *other).length()
+    }
+
+    fn distance_squared(&self, other: &Vec2) -> f32 {
+        (*self - *other).length_squared()
+    }
+
+    // Mirror image across a surface with unit `normal`: v - 2(v·n)n
+    fn reflect(&self, normal: &Vec2) -> Vec2 {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
+
     fn dot(&self, other: &Vec2) -> f32 {
         self.x * other.x + self.y * other.y
     }
+
+    // Scalar 2D cross product, positive when `other` is clockwise from self on screen (y down)
+    fn cross(&self, other: &Vec2) -> f32 {
+        self.x * other.y - self.y * other.x
+    }
+
+    // Rotated a quarter turn to the left-hand side: (x, y) -> (-y, x)
+    fn perp(&self) -> Vec2 {
+        Vec2::new(-self.y, self.x)
+    }
+
+    fn is_finite(&self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
 }
 
 impl std::ops::Add for Vec2 {
@@ -82,13 +494,171 @@ impl std::ops::Mul<f32> for Vec2 {
     }
 }
 
-#[derive(Clone)]
+impl std::ops::Div<f32> for Vec2 {
+    type Output = Vec2;
+    fn div(self, scalar: f32) -> Vec2 {
+        Vec2::new(self.x / scalar, self.y / scalar)
+    }
+}
+
+impl std::ops::AddAssign for Vec2 {
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::SubAssign for Vec2 {
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl std::ops::MulAssign<f32> for Vec2 {
+    fn mul_assign(&mut self, scalar: f32) {
+        *self = *self * scalar;
+    }
+}
+
+// Axis-aligned bounding box, used for cheap rejection before exact tests
+#[derive(Clone, Copy)]
+struct Aabb {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Aabb {
+    fn from_points(a: Vec2, b: Vec2) -> Self {
+        Self {
+            min: Vec2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Vec2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    fn around_circle(center: Vec2, radius: f32) -> Self {
+        Self {
+            min: Vec2::new(center.x - radius, center.y - radius),
+            max: Vec2::new(center.x + radius, center.y + radius),
+        }
+    }
+
+    fn expanded(&self, margin: f32) -> Self {
+        Self {
+            min: Vec2::new(self.min.x - margin, self.min.y - margin),
+            max: Vec2::new(self.max.x + margin, self.max.y + margin),
+        }
+    }
+
+    fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x <= other.max.x && self.max.x >= other.min.x
+            && self.min.y <= other.max.y && self.max.y >= other.min.y
+    }
+
+    fn contains_point(&self, point: Vec2) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x
+            && point.y >= self.min.y && point.y <= self.max.y
+    }
+}
+
+// A ball's footprint, shared by collision, goal and picking tests
+#[derive(Clone, Copy)]
+struct Circle {
+    center: Vec2,
+    radius: f32,
+}
+
+impl Circle {
+    // How far the two circles overlap, negative for the gap between them
+    fn penetration(&self, other: &Circle) -> f32 {
+        self.radius + other.radius - self.center.distance(&other.center)
+    }
+
+    // Exactly touching circles don't overlap
+    fn overlaps(&self, other: &Circle) -> bool {
+        self.penetration(other) > 0.0
+    }
+
+    fn contains(&self, point: Vec2) -> bool {
+        point.distance_squared(&self.center) <= self.radius * self.radius
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Wall {
     start: Vec2,
     end: Vec2,
     is_user_placed: bool,
+    // Overrides the ball's bounciness when set (0 = dead wall, >1 = booster)
+    #[serde(default)]
+    restitution: Option<f32>,
+    // Drawn width; balls bounce off the visible edge, not the centerline
+    #[serde(default = "default_wall_thickness")]
+    thickness: f32,
+    // Doors toggled by a switch are inactive while open: no collision, drawn faintly
+    #[serde(default = "default_wall_active")]
+    active: bool,
+}
+
+const WALL_THICKNESS: f32 = 6.0;
+
+fn default_wall_thickness() -> f32 {
+    WALL_THICKNESS
+}
+
+fn default_wall_active() -> bool {
+    true
+}
+
+// Pad that flips its target walls (doors) open or shut each time a ball rolls onto it
+#[derive(Clone, Serialize, Deserialize)]
+struct Switch {
+    pos: Vec2,
+    radius: f32,
+    target_wall_indices: Vec<usize>,
+    touched: bool, // A ball was on it last step, so it doesn't retrigger while held down
+    flipped: bool, // Pressed an odd number of times this attempt
+}
+
+const SWITCH_RADIUS: f32 = 12.0;
+
+impl Wall {
+    // Unit normal and signed distance from the centerline for a ball touching the wall's
+    // face, None when it's clear of it or past either end
+    fn contact(&self, center: Vec2, radius: f32) -> Option<(Vec2, f32)> {
+        let wall_vec = self.end - self.start;
+        let wall_len = wall_vec.length();
+        // A zero-length wall has no direction to push along
+        if wall_len <= 0.0 {
+            return None;
+        }
+        let wall_dir = wall_vec / wall_len;
+        let to_ball = center - self.start;
+        let along_wall = to_ball.dot(&wall_dir);
+        if along_wall < 0.0 || along_wall > wall_len {
+            return None;
+        }
+        let normal = wall_dir.perp();
+        let dist = to_ball.dot(&normal);
+        (dist.abs() <= radius + self.thickness / 2.0).then_some((normal, dist))
+    }
+}
+
+// Planning preview of the player's path: how far ahead it looks and how many wall
+// bounces it follows before stopping
+const PREDICTION_TIME: f32 = 3.0;
+const PREDICTION_BOUNCES: usize = 2;
+
+struct Prediction {
+    path: Vec<Vec2>,
+    first_contact: Option<usize>, // Object the path runs into, the ghost ball sits at the path's end
 }
 
+// Stretch or squeeze (px) at which a spring is drawn fully red or blue
+const SPRING_STRAIN_COLOR_RANGE: f32 = 40.0;
+
+// Half-size of the draggable square drawn on fixed spring anchors in the editor
+const ANCHOR_HANDLE_SIZE: f32 = 6.0;
+
+#[derive(Clone, Serialize, Deserialize)]
 struct Spring {
     object_index: usize,
     anchor: Option<usize>,
@@ -97,237 +667,1031 @@ struct Spring {
     stiffness: f32,
 }
 
-enum GameState {
-    Planning,
-    Simulating,
-    Won,
+// Jelly ball: a ring of point masses held in shape by springs to each other and a center
+#[derive(Clone)]
+struct SoftBody {
+    center: usize,
+    ring: Vec<usize>,
+    color: egui::Color32,
 }
 
-struct PhysicsApp {
-    objects: Vec<PhysicsObject>,
-    walls: Vec<Wall>,
-    springs: Vec<Spring>,
+const SOFT_BODY_POINT_MASS: f32 = 0.2;
+const SOFT_BODY_STIFFNESS: f32 = 80.0;
+
+// Region with its own gravity, e.g. a heavy floor band or a floaty ceiling
+#[derive(Clone)]
+struct GravityZone {
+    rect: Aabb,
     gravity: Vec2,
-    last_time: Instant,
-    bounds: (f32, f32),
-    level: u32,
-    game_state: GameState,
-    placing_wall: Option<Vec2>,
-    max_walls: usize,
-    win_time: Option<Instant>,
-    canvas_rect: egui::Rect,
 }
 
-impl Default for PhysicsApp {
-    fn default() -> Self {
-        let mut app = Self {
-            objects: Vec::new(),
-            walls: Vec::new(),
-            springs: Vec::new(),
-            gravity: Vec2::new(0.0, 400.0),
-            last_time: Instant::now(),
-            bounds: (800.0, 600.0),
-            level: 1,
-            game_state: GameState::Planning,
-            placing_wall: None,
-            max_walls: 3,
-            win_time: None,
-            canvas_rect: egui::Rect::NOTHING,
-        };
-        app.setup_level(1);
-        app
-    }
+// Size of a flip zone dropped from the editor
+const FLIP_ZONE_SIZE: Vec2 = Vec2 { x: 120.0, y: 80.0 };
+
+// A contact resolved during a step, with the impulse it applied
+struct CollisionEvent {
+    pos: Vec2,
+    impulse: f32,
+    a: usize,
+    b: Option<usize>, // None when `a` hit a wall
+    record: Option<CollisionRecord>, // Full before/after numbers, only captured in lesson mode
 }
 
-impl PhysicsApp {
-    fn setup_level(&mut self, level: u32) {
-        self.objects.clear();
-        self.walls.clear();
-        self.springs.clear();
-        self.game_state = GameState::Planning;
-        self.placing_wall = None;
-        self.win_time = None;
+// One ball-ball hit as the solver saw it, for the lesson overlay
+#[derive(Clone)]
+struct CollisionRecord {
+    time: f32,
+    a: usize,
+    b: usize,
+    masses: (f32, f32),
+    fixed: (bool, bool),
+    before: (Vec2, Vec2),
+    after: (Vec2, Vec2),
+    normal: Vec2, // From a toward b
+    restitution: f32,
+    impulse: f32,
+}
 
-        match level {
-            1 => self.setup_level_1(),
-            2 => self.setup_level_2(),
-            3 => self.setup_level_3(),
-            4 => self.setup_level_4(),
-                _ => {}
-        }
+// Hits kept in the lesson log, oldest dropped first
+const LESSON_LOG_LEN: usize = 20;
+
+// Hook for sounds, logging or analytics on hits, so the game itself needs no audio
+// dependency. Called once per hit above MIN_HIT_IMPULSE; both methods default to no-ops
+trait CollisionObserver {
+    fn on_collision(&mut self, _a: usize, _b: usize, _impulse: f32) {}
+    fn on_wall(&mut self, _obj: usize, _impulse: f32) {}
+}
+
+// Prints each hit, installed by `--log-collisions`
+struct LogObserver;
+
+impl CollisionObserver for LogObserver {
+    fn on_collision(&mut self, a: usize, b: usize, impulse: f32) {
+        println!("ball {a} hit ball {b}, impulse {impulse:.0}");
     }
 
-fn setup_level_1(&mut self) {
-        self.max_walls = 2;
-        
-        // Player ball - shoots into corner
-        self.objects.push(PhysicsObject {
-            pos: Vec2::new(220.0, 150.0),
-            vel: Vec2::new(420.0, 380.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 18.0,
-            mass: 1.0,
-            color: egui::Color32::from_rgb(100, 150, 255),
-            bounciness: 0.94,
-            is_goal: false,
-            is_player: true,
-            fixed: false,
-            initial_pos: Vec2::new(220.0, 150.0),
-            initial_vel: Vec2::new(420.0, 380.0),
-        });
+    fn on_wall(&mut self, obj: usize, impulse: f32) {
+        println!("ball {obj} hit a wall, impulse {impulse:.0}");
+    }
+}
 
-        // Heavy blocker in middle preventing direct shots
-        self.objects.push(PhysicsObject {
-            pos: Vec2::new(400.0, 300.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 65.0,
-            mass: 15.0,
-            color: egui::Color32::from_rgb(80, 80, 80),
-            bounciness: 0.1,
-            is_goal: false,
-            is_player: false,
-            fixed: true,
-            initial_pos: Vec2::new(400.0, 300.0),
-            initial_vel: Vec2::new(0.0, 0.0),
-        });
+// Player ball state after one physics step, for trajectory export
+#[derive(Clone)]
+struct TrajectorySample {
+    time: f32,
+    pos: Vec2,
+    vel: Vec2,
+}
+
+// Full copy of the dynamic state, so the sim can be rewound without re-simulating
+#[derive(Clone)]
+struct Snapshot {
+    time: f32,
+    steps: u32,
+    win_armed: bool,
+    objects: Vec<PhysicsObject>,
+    springs: Vec<Spring>,
+    satisfied_goals: HashSet<usize>,
+    switches: Vec<Switch>,
+    walls_active: Vec<bool>,
+}
+
+// Short-lived text floating near a contact point
+#[derive(Clone)]
+struct CollisionLabel {
+    pos: Vec2,
+    text: String,
+    life: f32, // seconds left, fades out as it reaches zero
+}
+
+const COLLISION_LABEL_LIFETIME: f32 = 0.5;
+
+// Resting contacts produce tiny impulses every step; anything weaker isn't a "hit"
+const MIN_HIT_IMPULSE: f32 = 20.0;
+
+// Collisions closer together than this (simulated seconds) extend the combo
+const COMBO_WINDOW: f32 = 0.75;
+const COMBO_POINTS: u32 = 10;
+
+// Repulsive objects ignore their real mass (which may be nonsensical, even negative)
+const REPULSIVE_INV_MASS: f32 = 1.0;
+const REPULSION_RANGE: f32 = 60.0; // gap between surfaces where the push starts
+const REPULSION_STRENGTH: f32 = 4000.0;
+
+// Impulse above which a plastic ball permanently dents
+const PLASTIC_IMPULSE_THRESHOLD: f32 = 300.0;
+
+// Breakables shatter after taking this much hit impulse in total, and the blast
+// shoves balls whose surface is within BLAST_RANGE (px) of theirs
+const BREAKABLE_HEALTH: f32 = 1500.0;
+const BLAST_RANGE: f32 = 100.0;
+const BLAST_IMPULSE: f32 = 600.0;
+
+// How the edges of the play area treat balls
+#[derive(Clone, Copy, PartialEq)]
+enum BoundaryMode {
+    ClosedBox,
+    OpenTop,       // no ceiling, balls can fly above the screen and fall back
+    WrapAround,    // leaving one edge re-enters from the opposite one
+    BottomlessPit, // no floor, balls that fall out are lost
+}
+
+// Awarded on a win by comparing the sim time with the level's thresholds
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    fn name(self) -> &'static str {
+        match self {
+            Medal::Bronze => "Bronze",
+            Medal::Silver => "Silver",
+            Medal::Gold => "Gold",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            Medal::Bronze => egui::Color32::from_rgb(205, 127, 50),
+            Medal::Silver => egui::Color32::from_rgb(192, 192, 200),
+            Medal::Gold => egui::Color32::from_rgb(255, 215, 0),
+        }
+    }
+}
+
+// A known-good answer for a level: the user walls to place and the player's launch velocity
+#[derive(Clone)]
+struct Replay {
+    walls: Vec<(Vec2, Vec2)>,
+    player_vel: Vec2,
+}
+
+impl Replay {
+    fn user_walls(&self) -> Vec<Wall> {
+        self.walls.iter().map(|&(start, end)| Wall {
+            start,
+            end,
+            is_user_placed: true,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
+        }).collect()
+    }
+}
+
+// Seconds without input in planning before level 1's solution plays as a background demo
+const ATTRACT_IDLE_TIME: f32 = 30.0;
+
+// Properties for the next ball dropped in the editor or from the console
+#[derive(Clone, Copy)]
+struct BallTemplate {
+    radius: f32,
+    mass: f32,
+    bounciness: f32,
+    density: Option<f32>, // When set, mass comes from density * area instead of `mass`
+}
+
+// Density that gives a radius 20 ball a mass of about 1, like the hand-built levels
+const DEFAULT_DENSITY: f32 = 0.0008;
+
+// Object kinds the editor can drop ready-made, matching how the hand-built levels set them up
+#[derive(Clone, Copy, PartialEq)]
+enum Archetype {
+    Player,
+    Intermediate,
+    Goal,
+    Blocker,
+    Pendulum, // Bob hung from a spring anchored PENDULUM_LENGTH above it
+}
+
+impl Archetype {
+    const ALL: [Archetype; 5] = [
+        Archetype::Player,
+        Archetype::Intermediate,
+        Archetype::Goal,
+        Archetype::Blocker,
+        Archetype::Pendulum,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Archetype::Player => "Player",
+            Archetype::Intermediate => "Intermediate",
+            Archetype::Goal => "Goal",
+            Archetype::Blocker => "Blocker",
+            Archetype::Pendulum => "Pendulum",
+        }
+    }
+}
+
+const PENDULUM_LENGTH: f32 = 100.0;
+const PENDULUM_STIFFNESS: f32 = 110.0;
+
+fn make_archetype(kind: Archetype, pos: Vec2) -> PhysicsObject {
+    let (radius, mass, color, bounciness) = match kind {
+        Archetype::Player => (18.0, 1.0, egui::Color32::from_rgb(100, 150, 255), 0.94),
+        Archetype::Intermediate => (24.0, 1.3, egui::Color32::from_rgb(255, 180, 100), 0.91),
+        Archetype::Goal => (30.0, 1.5, egui::Color32::from_rgb(100, 255, 100), 1.0),
+        Archetype::Blocker => (50.0, 15.0, egui::Color32::from_rgb(80, 80, 80), 0.1),
+        Archetype::Pendulum => (36.0, 2.2, egui::Color32::from_rgb(200, 100, 100), 0.78),
+    };
+    let mut object = PhysicsObject::ball(pos, radius, DEFAULT_DENSITY);
+    object.mass = mass;
+    object.color = color;
+    object.bounciness = bounciness;
+    object.initial_bounciness = bounciness;
+    object.is_player = kind == Archetype::Player;
+    object.is_goal = kind == Archetype::Goal;
+    object.fixed = kind == Archetype::Blocker;
+    object
+}
+
+// Springs and no-collide pairs dropped with a removed object, each with its old list position
+type RemovedLinks = (Vec<(usize, Spring)>, Vec<(usize, (usize, usize))>);
+
+// One undoable editor change. Executing a command returns the command that undoes it.
+#[derive(Clone)]
+enum EditorCommand {
+    AddWall { index: usize, wall: Wall },
+    RemoveWall { index: usize, wall: Wall },
+    AddObject { index: usize, object: PhysicsObject },
+    RemoveObject { index: usize, object: PhysicsObject },
+    RestoreLinks { springs: Vec<(usize, Spring)>, pairs: Vec<(usize, (usize, usize))> },
+    TogglePinned(usize),
+    ToggleRepulsive(usize),
+    TogglePlanet(usize),
+    ToggleBreakable(usize),
+    Resize { index: usize, radius: f32, mass: f32 },
+    ReplaceObject { index: usize, object: PhysicsObject },
+    MoveAnchor { spring: usize, pos: Vec2 },
+    AddFlipZone { index: usize, zone: Aabb },
+    AddSpring { index: usize, spring: Spring },
+    RemoveSpring { index: usize, spring: Spring },
+    AddSwitch { index: usize, pos: Vec2, target: usize },
+    RemoveSwitch { index: usize, pos: Vec2, target: usize },
+    RemoveFlipZone { index: usize, zone: Aabb },
+    AddSoftBody { center: Vec2, radius: f32, points: usize },
+    RemoveSoftBody { center: Vec2, radius: f32, points: usize, objects: Vec<usize> },
+    Batch(Vec<EditorCommand>),
+}
+
+// Per-level numbers for the end-of-run summary
+#[derive(Clone, Default, Serialize, Deserialize)]
+struct LevelStats {
+    attempts: u32,
+    walls_used: usize, // From the winning attempt
+    win_time: f32,     // Sim seconds of the winning attempt
+}
+
+// Hits harder than this shake the view for SHAKE_TIME seconds, by up to SHAKE_AMPLITUDE px
+const SHAKE_IMPULSE: f32 = 800.0;
+const SHAKE_TIME: f32 = 0.2;
+const SHAKE_AMPLITUDE: f32 = 6.0;
+
+// Editor wheel resizing: radius change per scrolled point, and the allowed range
+const RESIZE_PER_SCROLL: f32 = 0.0025;
+const MIN_EDIT_RADIUS: f32 = 4.0;
+const MAX_EDIT_RADIUS: f32 = 80.0;
+
+// Time for the medal to scale in after a win
+const MEDAL_TWEEN_TIME: f32 = 0.5;
+
+// Plunger launch: how far it can be pulled, how fast holding Space pulls it,
+// and launch speed (px/s) per pixel of pull
+const PLUNGER_MAX_PULL: f32 = 50.0;
+const PLUNGER_PULL_SPEED: f32 = 60.0;
+const PLUNGER_STRENGTH: f32 = 15.0;
+
+// Drag-to-aim: launch speed (px/s) per pixel dragged back from the player, and its limits
+const AIM_STRENGTH: f32 = 5.0;
+const AIM_MIN_SPEED: f32 = 20.0;
+const AIM_MAX_SPEED: f32 = 900.0;
+
+// Gap between a closing ball and the goal where the goal starts pulsing
+const GOAL_ALERT_DISTANCE: f32 = 120.0;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum GameState {
+    Planning,
+    Simulating,
+    Won,
+}
+
+// One object as saved, everything but what a step recomputes (sleep, debug arrows)
+#[derive(Serialize, Deserialize)]
+struct ObjectState {
+    pos: Vec2,
+    vel: Vec2,
+    initial_pos: Vec2,
+    initial_vel: Vec2,
+    radius: f32,
+    mass: f32,
+    color: [u8; 4],
+    bounciness: f32,
+    initial_bounciness: f32,
+    plasticity: f32,
+    is_player: bool,
+    is_goal: bool,
+    fixed: bool,
+    repulsive: bool,
+    planet: bool,
+    lost: bool,
+    gravity_flipped: bool,
+    angle: f32,
+    angular_vel: f32,
+    kinematic: Option<KinematicPath>,
+    health: Option<f32>,
+    damage: f32,
+    last_touched_by: Option<usize>,
+    spawn_id: Option<u64>,
+}
+
+// A whole play session, as opposed to a single level design
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    level: u32,
+    game_state: GameState,
+    // Level walls too, so open doors stay open and switch targets keep their indices
+    walls: Vec<Wall>,
+    switches: Vec<Switch>,
+    objects: Vec<ObjectState>,
+    // Saved with the objects since they point at them by index
+    springs: Vec<Spring>,
+    no_collide_pairs: Vec<(usize, usize)>,
+    soft_bodies: Vec<(usize, Vec<usize>)>, // Center and ring, the color comes back from the center
+    endless_seed: u64,
+    level_stats: HashMap<u32, LevelStats>,
+    best_medals: HashMap<u32, Medal>,
+    best_steps: HashMap<u32, u32>,
+    no_wall_wins: u32,
+    bonus_score: u32,
+}
+
+#[derive(Clone)]
+pub(crate) struct PhysicsApp {
+    objects: Vec<PhysicsObject>,
+    walls: Vec<Wall>,
+    springs: Vec<Spring>,
+    soft_bodies: Vec<SoftBody>,
+    gravity_zones: Vec<GravityZone>,
+    flip_zones: Vec<Aabb>, // Entering one flips a ball's gravity
+    switches: Vec<Switch>,
+    gravity: Vec2,
+    last_time: Instant,
+    bounds: (f32, f32),
+    level: u32,
+    game_state: GameState,
+    placing_wall: Option<Vec2>,
+    max_walls: usize,
+    // Fixed physics substeps per frame. More substeps keep stiff springs stable
+    // at the cost of running the whole step that many times per frame.
+    substeps: usize,
+    // Planetary levels: fixed objects pull on everything else instead of uniform gravity
+    orbital_mode: bool,
+    boundary_mode: BoundaryMode,
+    win_time: Option<f32>, // `clock` reading when the level was won
+    clock: f32, // Game seconds, only advances while unpaused; drives delays and animations
+    win_armed: bool, // Goal hits only count once the player ball has actually moved
+    paused: bool,
+    break_on_collision: bool, // Pause the moment a real hit happens
+    lesson_mode: bool, // Pause on each ball-ball hit and explain it with the solver's numbers
+    lesson_log: Vec<CollisionRecord>,
+    lesson_selected: Option<usize>, // Entry of `lesson_log` shown in the lesson window
+    editor_mode: bool,
+    measure_mode: bool, // Dragging draws a ruler instead of a wall
+    measure_start: Option<Vec2>,
+    status_message: Option<String>,
+    show_collision_values: bool,
+    show_prediction: bool, // Dotted preview of the player's path through the first wall bounces
+    debug_draw: bool, // Acceleration arrows on moving balls while simulating
+    collision_labels: Vec<CollisionLabel>,
+    endless_mode: bool, // Generate random levels after the hand-made ones
+    endless_seed: u64,
+    start_jitter: bool, // Nudge loose balls at level start so a memorized shot isn't enough
+    jitter_seed: u64,
+    jitter_rng: StdRng, // Seeded from `jitter_seed`, advances with every level start
+    record_trajectory: bool,
+    trajectory: Vec<TrajectorySample>,
+    history: VecDeque<Snapshot>, // Rewind buffer, newest at the back
+    record_history: bool, // Off for headless trial runs, which never rewind
+    checkpoint: Option<Snapshot>, // Player-set restore point within an attempt
+    sim_time: f32, // simulated seconds since launch, summed from step dt
+    rest_timer: f32, // How long every ball has been still during this attempt
+    nonfinite_resets: usize, // Balls put back at their start this attempt after going NaN or infinite
+    step_count: u32, // physics steps since launch, frame-rate independent
+    win_steps: u32, // step_count and sim_time at the moment of the win
+    win_sim_time: f32,
+    freeze_on_win: bool, // Hold the winning frame instead of letting the scene play on
+    practice_mode: bool, // Stay on a won level until the player moves on
+    best_steps: HashMap<u32, u32>, // Fewest steps to win per level
+    level_stats: HashMap<u32, LevelStats>, // This run's attempts and winning results
+    allow_sleeping: bool,
+    pub(crate) parallel_step: bool, // Only has an effect when built with the `parallel` feature
+    solver_iterations: usize, // Collision resolution passes per step, more = firmer stacks
+    stack_passes: usize, // Extra passes for islands with several contacts
+    min_substeps: usize, // Floor under the level's own substeps
+    preset: Preset,
+    restitution_floor: f32, // Rebounds slower than this (px/s) become resting contact
+    contact_damping: f32, // Fraction of velocity lost on every wall bounce, on top of restitution
+    no_collide_pairs: Vec<(usize, usize)>, // Object index pairs that pass through each other
+    goal_tolerance: f32, // Near-miss forgiveness (px) for the win check, at most CONTACT_MARGIN
+    drag_coefficient: f32, // Air drag, bigger balls feel more of it
+    leading_hemisphere_walls: bool, // Walls only push balls that are moving into them
+    separation_stiffness: f32, // 0..1, share of ball overlap pushed out per step
+    combo_mode: bool, // Arcade scoring layer for chained collisions
+    combo_count: u32,
+    last_combo_hit: f32,
+    bonus_score: u32,
+    no_wall_challenge: bool, // Wall budget forced to zero, levels must be won by the shot alone
+    no_wall_wins: u32,
+    medal_times: Option<[f32; 3]>, // Gold, silver and bronze limits in sim seconds
+    last_medal: Option<Medal>,
+    best_medals: HashMap<u32, Medal>,
+    thumbnails: Vec<egui::TextureHandle>, // Level select previews, built on first use
+    typed_wall: (Vec2, Vec2), // Endpoints in the "Place wall by coordinates" form
+    undo_stack: Vec<EditorCommand>, // Inverses of applied edits, newest last
+    redo_stack: Vec<EditorCommand>,
+    goal_tension: f32, // 0..1, how hard the goal outline pulses
+    satisfied_goals: HashSet<usize>, // Goals hit so far this attempt, the level wins once all are
+    winning_hit: Option<(usize, Option<usize>)>, // Ball that last satisfied a goal, and the player ball behind it
+    time_scale: f32, // Slow motion below 1, fast forward above
+    physics_accumulator: f32, // Sim time not yet stepped, carried to the next frame
+    next_ball: BallTemplate,
+    ball_texture: Option<egui::TextureHandle>, // Loaded at startup, missing when headless
+    textured_player: bool,
+    console_open: bool,
+    console_input: String,
+    console_log: Vec<String>,
+    console_history: Vec<String>, // Submitted commands, recalled with the up arrow
+    console_history_pos: usize,
+    plunger_mode: bool, // Launch by pulling a plunger instead of the level's preset shot
+    plunger_pull: f32,
+    lock_aim_direction: bool, // Aiming only sets launch speed, the level's direction stays
+    aiming: bool, // Dragging back from the player ball
+    dragging_anchor: Option<(usize, Vec2)>, // Spring being re-anchored in the editor, with where its anchor started
+    reference_solution: Option<Replay>, // Known-good answer, checked by `--verify-levels`
+    last_goal_gap: Option<f32>,
+    canvas_rect: egui::Rect,
+    scale_to_fit: bool, // Keep the designed play area and scale it into the canvas
+    world_transform: egui::emath::TSTransform, // World to screen, identity unless scaling to fit
+    screen_shake: bool, // Shake the view on heavy impacts
+    shake: f32, // Seconds of shake left, the offset fades with it
+    idle_since: Instant, // Last keyboard or mouse input
+    attract: Option<Box<PhysicsApp>>, // Demo drawn in place of the level while idle, dropped on any input
+    collision_observer: Option<Rc<RefCell<dyn CollisionObserver>>>, // Shared so clones of the app stay cheap
+    hint_solutions: HashMap<u32, Vec<Vec<Wall>>>, // Solver results per level, found on the first Hint press
+    hint_shown: Option<usize>, // Index of the cached solution drawn as the current hint
+    transition: Option<f32>, // Level change in progress, 0..1 fading out then 1..2 fading in; input is ignored meanwhile
+    transition_target: u32, // Level loaded at the midpoint of the transition
+    restart_armed_at: Option<Instant>, // First click on Restart Level while user walls would be lost
+    clear_armed_at: Option<Instant>, // Same for Clear User Walls
+    allowed_archetypes: &'static [Archetype], // Editor palette for this level
+    palette: Option<Archetype>, // What the editor drops, None for a custom ball from `next_ball`
+    pair_budget: usize, // Most ball pairs resolved per step, deeper and longer-waiting ones first
+    deferred_pairs: HashMap<(usize, usize), u32>, // Pairs left over last step and how many steps they've waited
+    max_speed: f32, // Speed cap (px/s) after integration, keeps spring blow-ups from tunneling
+    max_objects: usize, // Sandbox cap, past it new balls take over the oldest dropped ball's slot
+    next_spawn_id: u64,
+}
+
+impl Default for PhysicsApp {
+    fn default() -> Self {
+        let mut app = Self {
+            objects: Vec::new(),
+            walls: Vec::new(),
+            springs: Vec::new(),
+            soft_bodies: Vec::new(),
+            gravity_zones: Vec::new(),
+            flip_zones: Vec::new(),
+            switches: Vec::new(),
+            gravity: Vec2::new(0.0, 400.0),
+            last_time: Instant::now(),
+            bounds: DESIGN_BOUNDS,
+            level: 1,
+            game_state: GameState::Planning,
+            placing_wall: None,
+            max_walls: 3,
+            substeps: 1,
+            orbital_mode: false,
+            boundary_mode: BoundaryMode::ClosedBox,
+            win_time: None,
+            clock: 0.0,
+            win_armed: false,
+            paused: false,
+            break_on_collision: false,
+            lesson_mode: false,
+            lesson_log: Vec::new(),
+            lesson_selected: None,
+            editor_mode: false,
+            measure_mode: false,
+            measure_start: None,
+            status_message: None,
+            show_collision_values: false,
+            show_prediction: true,
+            debug_draw: false,
+            collision_labels: Vec::new(),
+            endless_mode: false,
+            endless_seed: 0,
+            start_jitter: false,
+            jitter_seed: 0,
+            jitter_rng: StdRng::seed_from_u64(0),
+            record_trajectory: false,
+            trajectory: Vec::new(),
+            history: VecDeque::new(),
+            record_history: true,
+            checkpoint: None,
+            sim_time: 0.0,
+            rest_timer: 0.0,
+            nonfinite_resets: 0,
+            step_count: 0,
+            win_steps: 0,
+            win_sim_time: 0.0,
+            freeze_on_win: false,
+            practice_mode: false,
+            best_steps: HashMap::new(),
+            level_stats: HashMap::new(),
+            allow_sleeping: true,
+            parallel_step: cfg!(feature = "parallel"),
+            solver_iterations: 1,
+            stack_passes: STACK_SOLVER_PASSES,
+            min_substeps: 1,
+            preset: Preset::Balanced,
+            restitution_floor: 20.0,
+            contact_damping: 0.0,
+            no_collide_pairs: Vec::new(),
+            goal_tolerance: 0.0,
+            drag_coefficient: 0.0,
+            leading_hemisphere_walls: true,
+            separation_stiffness: 1.0,
+            combo_mode: false,
+            combo_count: 0,
+            last_combo_hit: 0.0,
+            bonus_score: 0,
+            no_wall_challenge: false,
+            no_wall_wins: 0,
+            medal_times: None,
+            last_medal: None,
+            best_medals: HashMap::new(),
+            thumbnails: Vec::new(),
+            typed_wall: (Vec2::new(300.0, 300.0), Vec2::new(400.0, 300.0)),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            goal_tension: 0.0,
+            satisfied_goals: HashSet::new(),
+            winning_hit: None,
+            time_scale: 1.0,
+            physics_accumulator: 0.0,
+            next_ball: BallTemplate { radius: 20.0, mass: 1.0, bounciness: 0.8, density: None },
+            ball_texture: None,
+            textured_player: false,
+            console_open: false,
+            console_input: String::new(),
+            console_log: Vec::new(),
+            console_history: Vec::new(),
+            console_history_pos: 0,
+            plunger_mode: false,
+            plunger_pull: 0.0,
+            lock_aim_direction: false,
+            aiming: false,
+            dragging_anchor: None,
+            reference_solution: None,
+            last_goal_gap: None,
+            canvas_rect: egui::Rect::NOTHING,
+            scale_to_fit: false,
+            world_transform: egui::emath::TSTransform::IDENTITY,
+            screen_shake: true,
+            shake: 0.0,
+            idle_since: Instant::now(),
+            attract: None,
+            collision_observer: None,
+            hint_solutions: HashMap::new(),
+            hint_shown: None,
+            transition: None,
+            transition_target: 1,
+            restart_armed_at: None,
+            clear_armed_at: None,
+            allowed_archetypes: &[],
+            palette: None,
+            pair_budget: 2000,
+            deferred_pairs: HashMap::new(),
+            max_speed: 3000.0,
+            max_objects: 300,
+            next_spawn_id: 0,
+        };
+        app.setup_level(1);
+        app
+    }
+}
+
+impl PhysicsApp {
+    fn setup_level(&mut self, level: u32) {
+        self.objects.clear();
+        self.walls.clear();
+        self.springs.clear();
+        self.soft_bodies.clear();
+        self.gravity_zones.clear();
+        self.flip_zones.clear();
+        self.switches.clear();
+        self.game_state = GameState::Planning;
+        self.placing_wall = None;
+        self.measure_start = None;
+        self.dragging_anchor = None;
+        self.hint_shown = None;
+        self.plunger_pull = 0.0;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.win_time = None;
+        self.win_armed = false;
+        self.paused = false;
+        self.collision_labels.clear();
+        self.trajectory.clear();
+        self.history.clear();
+        self.checkpoint = None;
+        self.lesson_log.clear();
+        self.lesson_selected = None;
+        self.goal_tension = 0.0;
+        self.last_goal_gap = None;
+        self.satisfied_goals.clear();
+        self.winning_hit = None;
+        self.sim_time = 0.0;
+        self.rest_timer = 0.0;
+        self.nonfinite_resets = 0;
+        self.step_count = 0;
+        self.bonus_score = 0;
+        self.reset_combo();
+        self.substeps = 1;
+        self.medal_times = None;
+        self.last_medal = None;
+        self.contact_damping = 0.0;
+        self.no_collide_pairs.clear();
+        self.goal_tolerance = 0.0;
+        self.lock_aim_direction = false;
+        self.palette = None;
+        // Pendulums only where the level already builds with springs
+        self.allowed_archetypes = &Archetype::ALL[..4];
+        self.reference_solution = None;
+        self.orbital_mode = false;
+        self.boundary_mode = BoundaryMode::ClosedBox;
+
+        if level > LEVEL_COUNT && self.endless_mode {
+            self.generate_random_level(self.level_seed(level), level - LEVEL_COUNT);
+        } else if let Some(setup) = (level as usize).checked_sub(1).and_then(|i| LEVELS.get(i)) {
+            setup(self);
+        }
+        if self.start_jitter {
+            self.apply_start_jitter();
+        }
+        self.resolve_initial_overlaps();
+        self.apply_player_texture();
+    }
+
+    // Small random offsets to the start of every loose ball except the goal. Each jittered
+    // layout has to stay unsolved without walls yet beatable by the solver, otherwise
+    // it's rerolled, and after JITTER_TRIES or JITTER_TIME_LIMIT the level keeps its
+    // designed layout
+    fn apply_start_jitter(&mut self) {
+        let designed = self.objects.clone();
+        let mut solver_rng = StdRng::seed_from_u64(self.jitter_seed);
+        let deadline = Instant::now() + Duration::from_secs_f32(JITTER_TIME_LIMIT);
+        for _ in 0..JITTER_TRIES {
+            if Instant::now() > deadline {
+                break;
+            }
+            self.jitter_objects();
+            self.resolve_initial_overlaps();
+            if !self.wins_with(&[]) && self.find_solution(&mut solver_rng, Some(deadline)).is_some() {
+                // Cached hints were solved for another layout
+                self.hint_solutions.remove(&self.level);
+                return;
+            }
+            self.objects = designed.clone();
+        }
+    }
+
+    fn jitter_objects(&mut self) {
+        let jelly: HashSet<usize> = self.soft_bodies.iter()
+            .flat_map(|b| b.ring.iter().copied().chain(std::iter::once(b.center)))
+            .collect();
+        for (i, obj) in self.objects.iter_mut().enumerate() {
+            if obj.is_goal || obj.fixed || jelly.contains(&i) { continue; }
+            let offset = Vec2::new(
+                self.jitter_rng.random_range(-JITTER_POSITION..=JITTER_POSITION),
+                self.jitter_rng.random_range(-JITTER_POSITION..=JITTER_POSITION),
+            );
+            obj.initial_pos += offset;
+            obj.pos = obj.initial_pos;
+            if obj.initial_vel.length() > 0.0 {
+                let nudge = Vec2::new(
+                    self.jitter_rng.random_range(-JITTER_VELOCITY..=JITTER_VELOCITY),
+                    self.jitter_rng.random_range(-JITTER_VELOCITY..=JITTER_VELOCITY),
+                );
+                obj.initial_vel += nudge;
+                obj.vel = obj.initial_vel;
+            }
+        }
+    }
+
+    // Fade over to `level` instead of swapping the scene in one frame
+    fn change_level(&mut self, level: u32) {
+        if self.transition.is_none() {
+            self.transition = Some(0.0);
+            self.transition_target = level;
+        }
+    }
+
+    fn advance_transition(&mut self, frame_time: f32) {
+        if let Some(progress) = self.transition {
+            let next = progress + frame_time / TRANSITION_TIME;
+            if progress < 1.0 && next >= 1.0 {
+                self.level = self.transition_target;
+                self.setup_level(self.level);
+            }
+            self.transition = (next < 2.0).then_some(next);
+        }
+    }
+
+    // Whether there's a level after the current one to advance to
+    fn has_next_level(&self) -> bool {
+        self.level < LEVEL_COUNT || self.endless_mode
+    }
+
+    fn apply_player_texture(&mut self) {
+        let texture = self.ball_texture.clone().filter(|_| self.textured_player);
+        for obj in self.objects.iter_mut().filter(|o| o.is_player) {
+            obj.texture = texture.clone();
+        }
+    }
+
+    // Empty box filled with dynamic balls, already running, for benches/step.rs.
+    // Dense scenes pack balls into constant contact, sparse ones use tiny balls on the same grid
+    #[allow(dead_code)] // The game itself never builds one
+    pub(crate) fn benchmark_scene(count: usize, dense: bool) -> PhysicsApp {
+        let mut app = PhysicsApp::default();
+        app.objects.clear();
+        app.walls.clear();
+        app.springs.clear();
+        app.allow_sleeping = false;
+        app.game_state = GameState::Simulating;
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let columns = 40;
+        let radius = if dense { 8.0 } else { 2.0 };
+        for i in 0..count {
+            let pos = Vec2::new(
+                BOARDER_START + 15.0 + (i % columns) as f32 * 19.0,
+                30.0 + (i / columns) as f32 * 19.0,
+            );
+            let vel = Vec2::new(rng.random_range(-200.0..200.0), rng.random_range(-200.0..200.0));
+            app.objects.push(PhysicsObject {
+                vel,
+                mass: 1.0,
+                initial_vel: vel,
+                ..PhysicsObject::ball(pos, radius, DEFAULT_DENSITY)
+            });
+        }
+        app
+    }
+
+    fn level_seed(&self, level: u32) -> u64 {
+        self.endless_seed.wrapping_add(level as u64)
+    }
+
+    // Procedural level: more blockers and fewer walls as difficulty grows. Only layouts the
+    // solver has beaten are kept; if none turns up in time a hand-made level stands in
+    fn generate_random_level(&mut self, seed: u64, difficulty: u32) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let deadline = Instant::now() + Duration::from_secs_f32(GENERATOR_TIME_LIMIT);
+
+        for _ in 0..GENERATOR_TRIES {
+            if Instant::now() > deadline {
+                break;
+            }
+            self.objects.clear();
+            self.walls.clear();
+            self.max_walls = 3usize.saturating_sub(difficulty as usize / 3).max(1);
+
+            let (w, h) = self.bounds;
+            let player_pos = Vec2::new(
+                rng.random_range(BOARDER_START + 40.0..BOARDER_START + 0.25 * w),
+                rng.random_range(100.0..h - 100.0),
+            );
+            let player_vel = Vec2::new(rng.random_range(300.0..550.0), rng.random_range(-400.0..200.0));
+            self.objects.push(PhysicsObject {
+                vel: player_vel,
+                mass: 1.0,
+                color: egui::Color32::from_rgb(100, 150, 255),
+                bounciness: 0.95,
+                is_player: true,
+                initial_vel: player_vel,
+                initial_bounciness: 0.95,
+                ..PhysicsObject::ball(player_pos, 18.0, DEFAULT_DENSITY)
+            });
+
+            let intermediate_pos = Vec2::new(
+                rng.random_range(BOARDER_START + 0.35 * w..BOARDER_START + 0.65 * w),
+                rng.random_range(150.0..h - 150.0),
+            );
+            self.objects.push(PhysicsObject {
+                mass: 1.4,
+                color: egui::Color32::from_rgb(255, 180, 100),
+                bounciness: 0.9,
+                initial_bounciness: 0.9,
+                ..PhysicsObject::ball(intermediate_pos, 25.0, DEFAULT_DENSITY)
+            });
+
+            let goal_pos = Vec2::new(
+                rng.random_range(BOARDER_START + 0.7 * w..BOARDER_START + w - 40.0),
+                rng.random_range(150.0..h - 50.0),
+            );
+            self.objects.push(PhysicsObject {
+                mass: 1.6,
+                color: egui::Color32::from_rgb(100, 255, 100),
+                bounciness: 0.85,
+                is_goal: true,
+                initial_bounciness: 0.85,
+                ..PhysicsObject::ball(goal_pos, 30.0, DEFAULT_DENSITY)
+            });
+
+            let blocker_count = (1 + difficulty / 2).min(5);
+            for _ in 0..blocker_count {
+                let radius = rng.random_range(30.0..60.0);
+                // Skip spots that overlap something already placed
+                for _ in 0..20 {
+                    let pos = Vec2::new(
+                        rng.random_range(BOARDER_START + 0.2 * w..BOARDER_START + 0.9 * w),
+                        rng.random_range(radius..h - radius),
+                    );
+                    let clear = self.objects.iter()
+                        .all(|o| o.pos.distance(&pos) > o.radius + radius + 20.0);
+                    if clear {
+                        self.objects.push(PhysicsObject {
+                            mass: 20.0,
+                            color: egui::Color32::from_rgb(80, 80, 80),
+                            bounciness: 0.1,
+                            fixed: true,
+                            initial_bounciness: 0.1,
+                            ..PhysicsObject::ball(pos, radius, DEFAULT_DENSITY)
+                        });
+                        break;
+                    }
+                }
+            }
+
+            // Reject layouts that win without any walls; they aren't puzzles
+            if !self.wins_with(&[]) && self.find_solution(&mut rng, Some(deadline)).is_some() {
+                return;
+            }
+        }
+
+        // Never hand over an unchecked layout, the shipped levels are verified
+        self.objects.clear();
+        self.walls.clear();
+        LEVELS[(seed % LEVELS.len() as u64) as usize](self);
+    }
+
+fn setup_level_1(&mut self) {
+        self.max_walls = 2;
+        self.medal_times = Some([4.0, 6.0, 8.0]);
+        self.reference_solution = Some(Replay {
+            walls: vec![(Vec2::new(557.0, 402.0), Vec2::new(574.0, 301.0))],
+            player_vel: Vec2::new(420.0, 380.0),
+        });
+        self.goal_tolerance = 3.0;
+        // The bank shot angle is part of the puzzle, only its power is up to the player
+        self.lock_aim_direction = true;
+        
+        // Player ball - shoots into corner
+        let player = self.objects.len();
+        self.objects.push(PhysicsObject {
+            vel: Vec2::new(420.0, 380.0),
+            mass: 1.0,
+            color: egui::Color32::from_rgb(100, 150, 255),
+            bounciness: 0.94,
+            is_player: true,
+            initial_vel: Vec2::new(420.0, 380.0),
+            initial_bounciness: 0.94,
+            ..PhysicsObject::ball(Vec2::new(220.0, 150.0), 18.0, DEFAULT_DENSITY)
+        });
+
+        // Heavy blocker in middle preventing direct shots
+        self.objects.push(PhysicsObject {
+            mass: 15.0,
+            color: egui::Color32::from_rgb(80, 80, 80),
+            bounciness: 0.1,
+            fixed: true,
+            initial_bounciness: 0.1,
+            ..PhysicsObject::ball(Vec2::new(400.0, 300.0), 65.0, DEFAULT_DENSITY)
+        });
 
         // Intermediate ball - this MUST hit the goal
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(600.0, 180.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 24.0,
             mass: 1.3,
             color: egui::Color32::from_rgb(255, 180, 100),
             bounciness: 0.91,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(600.0, 180.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.91,
+            ..PhysicsObject::ball(Vec2::new(600.0, 180.0), 24.0, DEFAULT_DENSITY)
         });
 
         // Goal ball - can only be hit by intermediate ball
+        let goal = self.objects.len();
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(800.0, 480.0),
             vel: Vec2::new(0.0, 450.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 30.0,
             mass: 1.5,
             color: egui::Color32::from_rgb(100, 255, 100),
             bounciness: 1.0,
             is_goal: true,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(800.0, 480.0),
             initial_vel: Vec2::new(0.0,450.0),
+            initial_bounciness: 1.0,
+            ..PhysicsObject::ball(Vec2::new(800.0, 480.0), 30.0, DEFAULT_DENSITY)
         });
+        // The player has to knock the orange ball in, it passes straight through the goal
+        self.no_collide_pairs.push((player, goal));
 
         // Blocking wall creating narrow passage
         self.walls.push(Wall {
             start: Vec2::new(500.0, 350.0),
             end: Vec2::new(700.0, 330.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
     }
 
     fn setup_level_2(&mut self) {
         self.max_walls = 3;
+        self.medal_times = Some([4.0, 6.0, 8.0]);
+        self.reference_solution = Some(Replay {
+            walls: vec![
+                (Vec2::new(873.0, 460.0), Vec2::new(742.0, 318.0)),
+                (Vec2::new(877.0, 190.0), Vec2::new(970.0, 170.0)),
+                (Vec2::new(441.0, 265.0), Vec2::new(372.0, 248.0)),
+            ],
+            player_vel: Vec2::new(280.0, -520.0),
+        });
+        self.goal_tolerance = 3.0;
         
         // Player ball - awkward upward angle
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(100.0, 480.0),
             vel: Vec2::new(280.0, -520.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 19.0,
             mass: 1.0,
             color: egui::Color32::from_rgb(100, 150, 255),
             bounciness: 0.95,
-            is_goal: false,
             is_player: true,
-            fixed: false,
-            initial_pos: Vec2::new(100.0, 480.0),
             initial_vel: Vec2::new(280.0, -520.0),
+            initial_bounciness: 0.95,
+            ..PhysicsObject::ball(Vec2::new(100.0, 480.0), 19.0, DEFAULT_DENSITY)
         });
 
         // Large immovable blockers
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(250.0, 300.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 55.0,
             mass: 20.0,
             color: egui::Color32::from_rgb(90, 90, 90),
             bounciness: 0.05,
-            is_goal: false,
-            is_player: false,
             fixed: true,
-            initial_pos: Vec2::new(250.0, 300.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.05,
+            ..PhysicsObject::ball(Vec2::new(250.0, 300.0), 55.0, DEFAULT_DENSITY)
         });
 
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(550.0, 250.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 60.0,
             mass: 20.0,
             color: egui::Color32::from_rgb(90, 90, 90),
             bounciness: 0.05,
-            is_goal: false,
-            is_player: false,
             fixed: true,
-            initial_pos: Vec2::new(550.0, 250.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.05,
+            ..PhysicsObject::ball(Vec2::new(550.0, 250.0), 60.0, DEFAULT_DENSITY)
         });
 
         // First intermediate(blue) - player must hit this
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(400.0, 250.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 26.0,
             mass: 1.4,
             color: egui::Color32::from_rgb(100, 200, 200),
             bounciness: 0.92,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(400.0, 250.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.92,
+            ..PhysicsObject::ball(Vec2::new(400.0, 250.0), 26.0, DEFAULT_DENSITY)
         });
 
         // Second intermediate - first ball must hit this
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(650.0, 400.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 28.0,
             mass: 1.5,
             color: egui::Color32::from_rgb(255, 200, 120),
             bounciness: 0.90,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(650.0, 400.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.90,
+            ..PhysicsObject::ball(Vec2::new(650.0, 400.0), 28.0, DEFAULT_DENSITY)
         });
 
         // Goal ball - tucked in corner
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(850.0, 520.0),
             vel: Vec2::new(0.0, 450.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 32.0,
             mass: 1.7,
             color: egui::Color32::from_rgb(100, 255, 100),
             bounciness: 0.83,
             is_goal: true,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(850.0, 520.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.83,
+            ..PhysicsObject::ball(Vec2::new(850.0, 520.0), 32.0, DEFAULT_DENSITY)
         });
 
         // Barrier walls
@@ -335,37 +1699,52 @@ fn setup_level_1(&mut self) {
             start: Vec2::new(350.0, 450.0),
             end: Vec2::new(500.0, 430.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
 
         self.walls.push(Wall {
             start: Vec2::new(760.0,400.0),
             end: Vec2::new(760.0, 550.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
         self.walls.push(Wall {
             start: Vec2::new(760.0,150.0),
             end: Vec2::new(760.0, 300.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
     }
 
     fn setup_level_3(&mut self) {
         self.max_walls = 3;
+        self.medal_times = Some([2.5, 4.0, 6.0]);
+        self.reference_solution = Some(Replay {
+            walls: vec![
+                (Vec2::new(512.0, 471.0), Vec2::new(562.0, 586.0)),
+                (Vec2::new(689.0, 443.0), Vec2::new(829.0, 578.0)),
+            ],
+            player_vel: Vec2::new(440.0, -300.0),
+        });
+        self.substeps = 4; // stiff pendulums need smaller steps to stay stable
+        self.allowed_archetypes = &Archetype::ALL;
         
         // Player ball - diagonal shot
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(100.0, 500.0),
             vel: Vec2::new(440.0, -300.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 19.0,
             mass: 1.0,
             color: egui::Color32::from_rgb(100, 150, 255),
             bounciness: 0.96,
-            is_goal: false,
             is_player: true,
-            fixed: false,
-            initial_pos: Vec2::new(100.0, 500.0),
             initial_vel: Vec2::new(440.0, -300.0),
+            initial_bounciness: 0.96,
+            ..PhysicsObject::ball(Vec2::new(100.0, 500.0), 19.0, DEFAULT_DENSITY)
         });
 
         // Three fast-moving pendulums creating chaos
@@ -376,18 +1755,13 @@ fn setup_level_1(&mut self) {
 
         for (i, &(x, y, vel_x, rest_len, stiffness)) in pendulum_configs.iter().enumerate() {
             self.objects.push(PhysicsObject {
-                pos: Vec2::new(x, y),
                 vel: Vec2::new(vel_x, 0.0),
-                acc: Vec2::new(0.0, 0.0),
-                radius: 38.0 + i as f32 * 3.0,
                 mass: 2.2 + i as f32 * 0.4,
                 color: egui::Color32::from_rgb(200 - i as u8 * 30, 100, 100 + i as u8 * 40),
                 bounciness: 0.78,
-                is_goal: false,
-                is_player: false,
-                fixed: false,
-                initial_pos: Vec2::new(x, y),
                 initial_vel: Vec2::new(vel_x, 0.0),
+                initial_bounciness: 0.78,
+                ..PhysicsObject::ball(Vec2::new(x, y), 38.0 + i as f32 * 3.0, DEFAULT_DENSITY)
             });
 
             self.springs.push(Spring {
@@ -401,34 +1775,22 @@ fn setup_level_1(&mut self) {
 
         // Trigger ball that must be hit
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(700.0, 220.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 27.0,
             mass: 1.6,
             color: egui::Color32::from_rgb(255, 200, 100),
             bounciness: 0.5,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
             initial_pos: Vec2::new(700.0, 200.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.5,
+            ..PhysicsObject::ball(Vec2::new(700.0, 220.0), 27.0, DEFAULT_DENSITY)
         });
 
         // Goal ball
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(700.0, 430.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 32.0,
             mass: 1.8,
             color: egui::Color32::from_rgb(100, 255, 100),
             bounciness: 0.84,
             is_goal: true,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(700.0, 430.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.84,
+            ..PhysicsObject::ball(Vec2::new(700.0, 430.0), 32.0, DEFAULT_DENSITY)
         });
 
 
@@ -441,6 +1803,9 @@ fn setup_level_1(&mut self) {
             start: Vec2::new(600.0, 400.0),
             end: Vec2::new(600.0, 700.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
 
 
@@ -449,32 +1814,40 @@ fn setup_level_1(&mut self) {
             start: Vec2::new(660.0, 480.0),
             end: Vec2::new(760.0, 480.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
         // Protection walls
         self.walls.push(Wall {
             start: Vec2::new(680.0, 250.0),
             end: Vec2::new(770.0, 250.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
     }
 
     fn setup_level_4(&mut self) {
+        self.allowed_archetypes = &Archetype::ALL;
         self.max_walls = 2;
+        self.medal_times = Some([3.5, 5.0, 7.0]);
+        self.reference_solution = Some(Replay {
+            walls: vec![(Vec2::new(354.0, 165.0), Vec2::new(540.0, 101.0))],
+            player_vel: Vec2::new(500.0, -120.0),
+        });
         
         // Player ball
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(100.0, 300.0),
             vel: Vec2::new(500.0, -120.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 18.0,
             mass: 1.0,
             color: egui::Color32::from_rgb(100, 150, 255),
             bounciness: 0.97,
-            is_goal: false,
             is_player: true,
-            fixed: false,
-            initial_pos: Vec2::new(100.0, 300.0),
             initial_vel: Vec2::new(500.0, -120.0),
+            initial_bounciness: 0.97,
+            ..PhysicsObject::ball(Vec2::new(100.0, 300.0), 18.0, DEFAULT_DENSITY)
         });
 
         // Massive blockers creating narrow passages
@@ -486,35 +1859,28 @@ fn setup_level_1(&mut self) {
 
         for &(x, y, radius) in blockers.iter() {
             self.objects.push(PhysicsObject {
-                pos: Vec2::new(x, y),
-                vel: Vec2::new(0.0, 0.0),
-                acc: Vec2::new(0.0, 0.0),
-                radius,
                 mass: 25.0,
                 color: egui::Color32::from_rgb(70, 70, 70),
                 bounciness: 0.08,
-                is_goal: false,
-                is_player: false,
                 fixed: true,
-                initial_pos: Vec2::new(x, y),
-                initial_vel: Vec2::new(0.0, 0.0),
+                initial_bounciness: 0.08,
+                ..PhysicsObject::ball(Vec2::new(x, y), radius, DEFAULT_DENSITY)
             });
         }
+        // The last blocker slides up and back, opening and closing the passage under it
+        if let Some(blocker) = self.objects.last_mut() {
+            blocker.kinematic = Some(KinematicPath { waypoints: vec![Vec2::new(500.0, 240.0)], speed: 30.0 });
+        }
 
         // Moving pendulum obstacle in the path
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(450.0, 150.0),
             vel: Vec2::new(100.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 35.0,
             mass: 2.0,
             color: egui::Color32::from_rgb(255, 100, 100),
             bounciness: 0.90,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
             initial_pos: Vec2::new(400.0, 150.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.90,
+            ..PhysicsObject::ball(Vec2::new(450.0, 150.0), 35.0, DEFAULT_DENSITY)
         });
 
         self.springs.push(Spring {
@@ -528,34 +1894,21 @@ fn setup_level_1(&mut self) {
 
         //  trigger
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(580.0, 164.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 27.0,
             mass: 1.5,
             color: egui::Color32::from_rgb(255, 220, 120),
             bounciness: 0.92,
-            is_goal: false,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(580.0, 164.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.92,
+            ..PhysicsObject::ball(Vec2::new(580.0, 164.0), 27.0, DEFAULT_DENSITY)
         });
 
         // Goal
         self.objects.push(PhysicsObject {
-            pos: Vec2::new(760.0, 520.0),
-            vel: Vec2::new(0.0, 0.0),
-            acc: Vec2::new(0.0, 0.0),
-            radius: 34.0,
             mass: 2.0,
             color: egui::Color32::from_rgb(100, 255, 100),
             bounciness: 0.82,
             is_goal: true,
-            is_player: false,
-            fixed: false,
-            initial_pos: Vec2::new(760.0, 520.0),
-            initial_vel: Vec2::new(0.0, 0.0),
+            initial_bounciness: 0.82,
+            ..PhysicsObject::ball(Vec2::new(760.0, 520.0), 34.0, DEFAULT_DENSITY)
         });
 
         // Maze walls
@@ -563,12 +1916,18 @@ fn setup_level_1(&mut self) {
             start: Vec2::new(380.0, 320.0),
             end: Vec2::new(480.0, 280.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
     
         self.walls.push(Wall {
             start: Vec2::new(730.0, 570.0),
             end: Vec2::new(730.0, 300.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
 
         // nice wall
@@ -576,260 +1935,2342 @@ fn setup_level_1(&mut self) {
             start: Vec2::new(550.0, 190.0),
             end: Vec2::new(650.0, 190.0),
             is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
         });
         
     }
 
+    fn setup_level_5(&mut self) {
+        self.max_walls = 3;
+        self.medal_times = Some([3.0, 5.0, 7.0]);
+        self.reference_solution = Some(Replay {
+            walls: vec![(Vec2::new(841.0, 479.0), Vec2::new(719.0, 368.0))],
+            player_vel: Vec2::new(0.0, 0.0),
+        });
+
+        // Player ball - knocks the two hitters apart
+        self.objects.push(PhysicsObject {
+            mass: 1.0,
+            color: egui::Color32::from_rgb(100, 150, 255),
+            bounciness: 0.9,
+            is_player: true,
+            initial_bounciness: 0.9,
+            ..PhysicsObject::ball(Vec2::new(610.0, 80.0), 18.0, DEFAULT_DENSITY)
+        });
+
+        // Left hitter - must reach the left goal
+        self.objects.push(PhysicsObject {
+            mass: 1.2,
+            color: egui::Color32::from_rgb(255, 180, 100),
+            bounciness: 0.9,
+            initial_bounciness: 0.9,
+            ..PhysicsObject::ball(Vec2::new(585.0, 305.0), 22.0, DEFAULT_DENSITY)
+        });
+
+        // Right hitter - must reach the right goal
+        self.objects.push(PhysicsObject {
+            mass: 1.2,
+            color: egui::Color32::from_rgb(255, 180, 100),
+            bounciness: 0.9,
+            initial_bounciness: 0.9,
+            ..PhysicsObject::ball(Vec2::new(635.0, 305.0), 22.0, DEFAULT_DENSITY)
+        });
+
+        // Left goal
+        self.objects.push(PhysicsObject {
+            mass: 1.5,
+            color: egui::Color32::from_rgb(100, 255, 100),
+            bounciness: 0.5,
+            is_goal: true,
+            initial_bounciness: 0.5,
+            ..PhysicsObject::ball(Vec2::new(260.0, 570.0), 25.0, DEFAULT_DENSITY)
+        });
+
+        // Right goal
+        self.objects.push(PhysicsObject {
+            mass: 1.5,
+            color: egui::Color32::from_rgb(100, 255, 100),
+            bounciness: 0.5,
+            is_goal: true,
+            initial_bounciness: 0.5,
+            ..PhysicsObject::ball(Vec2::new(960.0, 570.0), 25.0, DEFAULT_DENSITY)
+        });
+
+        // Ledge the hitters rest on
+        self.walls.push(Wall {
+            start: Vec2::new(530.0, 330.0),
+            end: Vec2::new(690.0, 330.0),
+            is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
+        });
+    }
+
      
     
-    fn count_user_walls(&self) -> usize {
-        self.walls.iter().filter(|w| w.is_user_placed).count()
+    fn get_object_at_pos(&self, pos: Vec2) -> Option<usize> {
+        self.objects.iter().position(|obj| obj.circle().contains(pos))
     }
 
-    fn reset_simulation(&mut self) {
-        for obj in &mut self.objects {
-            obj.pos = obj.initial_pos;
-            obj.vel = obj.initial_vel;
-            obj.acc = Vec2::new(0.0, 0.0);
-        }
-        self.game_state = GameState::Planning;
-        self.win_time = None;
+    // Spring whose fixed anchor handle is under `pos`, object-anchored springs have no handle
+    fn get_anchor_at_pos(&self, pos: Vec2) -> Option<usize> {
+        self.springs.iter().position(|s| {
+            s.anchor.is_none() && Circle { center: s.anchor_pos, radius: ANCHOR_HANDLE_SIZE }.contains(pos)
+        })
     }
 
-    fn update_physics(&mut self, dt: f32) {
-        if !matches!(self.game_state, GameState::Simulating) {
-            return;
+    fn move_anchor(&mut self, spring: usize, pos: Vec2) {
+        self.springs[spring].anchor_pos = pos;
+        let object_index = self.springs[spring].object_index;
+        if let Some(obj) = self.objects.get_mut(object_index) {
+            obj.wake();
         }
+    }
 
-        // Apply spring forces
-        let spring_forces: Vec<(usize, Vec2)> = self.springs.iter().filter_map(|spring| {
-            let obj = self.objects.get(spring.object_index)?;
-            
-            let anchor_pos = if let Some(anchor_idx) = spring.anchor {
-                self.objects.get(anchor_idx)?.pos
-            } else {
-                spring.anchor_pos
-            };
+    fn push_point_mass(&mut self, pos: Vec2, radius: f32, color: egui::Color32) -> usize {
+        self.objects.push(PhysicsObject {
+            mass: SOFT_BODY_POINT_MASS,
+            color,
+            bounciness: 0.6,
+            initial_bounciness: 0.6,
+            ..PhysicsObject::ball(pos, radius, DEFAULT_DENSITY)
+        });
+        self.objects.len() - 1
+    }
 
-            let to_anchor = anchor_pos - obj.pos;
-            let distance = to_anchor.length();
-            if distance == 0.0 { return None; }
+    // Springs only push their own object, so each link is a pair of opposing springs
+    fn link_with_springs(&mut self, a: usize, b: usize) {
+        let rest_length = self.objects[a].pos.distance(&self.objects[b].pos);
+        for (object_index, anchor) in [(a, b), (b, a)] {
+            self.springs.push(Spring {
+                object_index,
+                anchor: Some(anchor),
+                anchor_pos: Vec2::new(0.0, 0.0),
+                rest_length,
+                stiffness: SOFT_BODY_STIFFNESS,
+            });
+        }
+    }
 
-            let direction = to_anchor * (1.0 / distance);
-            let stretch = distance - spring.rest_length;
-            let spring_force = direction * (stretch * spring.stiffness);
+    fn spawn_soft_body(&mut self, center: Vec2, radius: f32, points: usize) {
+        let points = points.max(3);
+        let color = egui::Color32::from_rgb(230, 120, 220);
+        // Keep neighbouring points from touching at rest, or collisions fight the springs
+        let spacing = std::f32::consts::TAU * radius / points as f32;
+        let point_radius = (spacing * 0.45).min(radius * 0.5);
 
-            Some((spring.object_index, spring_force))
+        let center_idx = self.push_point_mass(center, point_radius, color);
+        let ring: Vec<usize> = (0..points).map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / points as f32;
+            let pos = center + Vec2::from_angle(angle) * radius;
+            self.push_point_mass(pos, point_radius, color)
         }).collect();
 
-        for (idx, force) in spring_forces {
-            if let Some(obj) = self.objects.get_mut(idx) {
-                if !obj.fixed {
-                    obj.acc = obj.acc + force * (1.0 / obj.mass);
-                }
-            }
+        for i in 0..points {
+            self.link_with_springs(ring[i], ring[(i + 1) % points]);
+            self.link_with_springs(ring[i], center_idx);
         }
 
-        // Update physics for all objects
-        for obj in &mut self.objects {
-            if !obj.fixed {
-                obj.acc = obj.acc + self.gravity;
-                obj.vel = obj.vel + obj.acc * dt;
-                obj.acc = Vec2::new(0.0, 0.0);
-                obj.pos = obj.pos + obj.vel * dt;
-            }
+        self.soft_bodies.push(SoftBody { center: center_idx, ring, color });
+    }
+
+    // Editor: toggle the repulsive gimmick on a ball
+    fn toggle_repulsive(&mut self, idx: usize) {
+        if let Some(obj) = self.objects.get_mut(idx) {
+            obj.repulsive = !obj.repulsive;
+        }
+    }
+
+    // Editor: make a ball pull the others in orbital mode, or stop it pulling
+    fn toggle_planet(&mut self, idx: usize) {
+        if let Some(obj) = self.objects.get_mut(idx) {
+            obj.planet = !obj.planet;
         }
+    }
 
-        // Boundary collisions
+    fn toggle_breakable(&mut self, idx: usize) {
+        if let Some(obj) = self.objects.get_mut(idx) {
+            obj.health = if obj.health.is_some() { None } else { Some(BREAKABLE_HEALTH) };
+        }
+    }
+
+    // Radial shove from a shattered breakable, strongest right at its surface
+    fn blast(&mut self, center: Vec2, radius: f32) {
         for obj in &mut self.objects {
-            const boarder_start: f32 = 210.0;
-            if obj.fixed { continue; }
-            
-            if obj.pos.x - obj.radius < boarder_start {
-                obj.pos.x = obj.radius + boarder_start;
-                obj.vel.x = -obj.vel.x * obj.bounciness;
-            } else if obj.pos.x + obj.radius > self.bounds.0 + boarder_start {
-                obj.pos.x = self.bounds.0 - obj.radius + boarder_start;
-                obj.vel.x = -obj.vel.x * obj.bounciness;
+            if obj.fixed || obj.lost { continue; }
+            let away = obj.pos - center;
+            let gap = away.length() - radius - obj.radius;
+            if gap < BLAST_RANGE {
+                let falloff = 1.0 - gap.max(0.0) / BLAST_RANGE;
+                obj.wake();
+                obj.vel += away.normalized() * (BLAST_IMPULSE * falloff * obj.inv_mass());
             }
+        }
+    }
 
-            if obj.pos.y - obj.radius < 15.0 {
-                obj.pos.y = obj.radius;
-                obj.vel.y = -obj.vel.y * obj.bounciness;
-            } else if obj.pos.y + obj.radius > self.bounds.1 {
-                obj.pos.y = self.bounds.1 - obj.radius;
-                obj.vel.y = -obj.vel.y * obj.bounciness;
-            }
+    // Editor: turn a dynamic ball into an immovable blocker or back again
+    fn toggle_pinned(&mut self, idx: usize) {
+        if let Some(obj) = self.objects.get_mut(idx) {
+            obj.fixed = !obj.fixed;
+            obj.vel = if obj.fixed { Vec2::new(0.0, 0.0) } else { obj.initial_vel };
+            obj.acc = Vec2::new(0.0, 0.0);
         }
+    }
 
-        // Object-to-object collisions
-        let len = self.objects.len();
-        for i in 0..len {
-            for j in (i + 1)..len {
-                let (obj1, obj2) = {
-                    let (left, right) = self.objects.split_at_mut(j);
-                    (&mut left[i], &mut right[0])
-                };
+    fn play_area(&self) -> Aabb {
+        Aabb::from_points(
+            Vec2::new(BOARDER_START, 0.0),
+            Vec2::new(BOARDER_START + self.bounds.0, self.bounds.1),
+        )
+    }
 
-                let delta_pos = obj2.pos - obj1.pos;
-                let dist = delta_pos.length();
-                let min_dist = obj1.radius + obj2.radius;
+    fn count_user_walls(&self) -> usize {
+        self.walls.iter().filter(|w| w.is_user_placed).count()
+    }
 
-                if dist < min_dist {
-                    // Check for goal hit
-                    if (obj1.is_goal && (!obj2.is_player && !obj2.fixed) || (obj2.is_goal && (!obj1.is_player && !obj1.fixed))) {
-                        if !matches!(self.game_state, GameState::Won) {
-                            self.game_state = GameState::Won;
-                            self.win_time = Some(Instant::now());
-                        }
-                    }
+    // Shared placement rules for mouse-drawn and typed-in walls
+    fn try_add_user_wall(&mut self, start: Vec2, end: Vec2) -> Result<(), &'static str> {
+        if self.count_user_walls() >= self.wall_budget() {
+            return Err("No walls left in the budget");
+        }
+        if !self.play_area().contains_point(start) || !self.play_area().contains_point(end) {
+            return Err("Wall must be inside the play area");
+        }
+        // Only add wall if it's long enough
+        if start.distance(&end) <= 20.0 {
+            return Err("Wall is too short");
+        }
+        let wall = Wall {
+            start,
+            end,
+            is_user_placed: true,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
+        };
+        self.edit(EditorCommand::AddWall { index: self.walls.len(), wall });
+        Ok(())
+    }
 
-                    let normal = delta_pos.normalized();
-                    let overlap = min_dist - dist;
-                    let separation = normal * (overlap / 2.0);
-                    let total_mass = obj1.mass + obj2.mass;
-                    
-                    if !obj1.fixed {
-                        obj1.pos = obj1.pos - separation * (obj2.mass / total_mass);
-                    }
-                    if !obj2.fixed {
-                        obj2.pos = obj2.pos + separation * (obj1.mass / total_mass);
-                    }
+    fn clear_user_walls(&mut self) {
+        // Highest index first so the recorded indices stay valid while removing
+        let removals: Vec<EditorCommand> = self.walls.iter().enumerate().rev()
+            .filter(|(_, w)| w.is_user_placed)
+            .map(|(index, wall)| EditorCommand::RemoveWall { index, wall: wall.clone() })
+            .collect();
+        if !removals.is_empty() {
+            self.edit(EditorCommand::Batch(removals));
+        }
+    }
+
+    // Apply a user edit and make it undoable
+    fn edit(&mut self, command: EditorCommand) {
+        let inverse = self.execute(command);
+        self.undo_stack.push(inverse);
+        self.redo_stack.clear();
+    }
 
-                    let rel_vel = obj2.vel - obj1.vel;
-                    let vel_along_normal = rel_vel.dot(&normal);
+    fn undo(&mut self) {
+        if let Some(command) = self.undo_stack.pop() {
+            let inverse = self.execute(command);
+            self.redo_stack.push(inverse);
+        }
+    }
 
-                    let least_bounciness = obj1.bounciness.min(obj2.bounciness);
-                    let mut impulse_mag = -(1.0 + least_bounciness) * vel_along_normal;
-                    impulse_mag = impulse_mag / (1.0 / obj1.mass + 1.0 / obj2.mass);
+    fn redo(&mut self) {
+        if let Some(command) = self.redo_stack.pop() {
+            let inverse = self.execute(command);
+            self.undo_stack.push(inverse);
+        }
+    }
 
-                    if !obj1.fixed {
-                        obj1.vel = obj1.vel - (normal * impulse_mag) * (1.0 / obj1.mass);
-                    }
-                    if !obj2.fixed {
-                        obj2.vel = obj2.vel + (normal * impulse_mag) * (1.0 / obj2.mass);
-                    }
+    // Perform a command and return its inverse
+    fn execute(&mut self, command: EditorCommand) -> EditorCommand {
+        match command {
+            EditorCommand::AddWall { index, wall } => {
+                self.walls.insert(index, wall.clone());
+                EditorCommand::RemoveWall { index, wall }
+            }
+            EditorCommand::RemoveWall { index, wall } => {
+                self.walls.remove(index);
+                EditorCommand::AddWall { index, wall }
+            }
+            EditorCommand::AddObject { index, object } => {
+                self.insert_object(index, object.clone());
+                EditorCommand::RemoveObject { index, object }
+            }
+            EditorCommand::RemoveObject { index, object } => {
+                let (springs, pairs) = self.remove_object(index);
+                if springs.is_empty() && pairs.is_empty() {
+                    EditorCommand::AddObject { index, object }
+                } else {
+                    EditorCommand::Batch(vec![
+                        EditorCommand::AddObject { index, object },
+                        EditorCommand::RestoreLinks { springs, pairs },
+                    ])
+                }
+            }
+            EditorCommand::RestoreLinks { springs, pairs } => {
+                // Ascending order, so each link lands back at the position it was taken from
+                for (index, spring) in springs {
+                    self.springs.insert(index, spring);
+                }
+                for (index, pair) in pairs {
+                    self.no_collide_pairs.insert(index, pair);
+                }
+                // Removing the object again drops the links with it, nothing left to undo here
+                EditorCommand::Batch(Vec::new())
+            }
+            EditorCommand::TogglePinned(idx) => {
+                self.toggle_pinned(idx);
+                EditorCommand::TogglePinned(idx)
+            }
+            EditorCommand::ToggleRepulsive(idx) => {
+                self.toggle_repulsive(idx);
+                EditorCommand::ToggleRepulsive(idx)
+            }
+            EditorCommand::TogglePlanet(idx) => {
+                self.toggle_planet(idx);
+                EditorCommand::TogglePlanet(idx)
+            }
+            EditorCommand::ToggleBreakable(idx) => {
+                self.toggle_breakable(idx);
+                EditorCommand::ToggleBreakable(idx)
+            }
+            EditorCommand::Resize { index, radius, mass } => {
+                let obj = &mut self.objects[index];
+                let undo = EditorCommand::Resize { index, radius: obj.radius, mass: obj.mass };
+                obj.radius = radius;
+                obj.mass = mass;
+                undo
+            }
+            EditorCommand::ReplaceObject { index, object } => {
+                let old = std::mem::replace(&mut self.objects[index], object);
+                EditorCommand::ReplaceObject { index, object: old }
+            }
+            EditorCommand::MoveAnchor { spring, pos } => {
+                let undo = EditorCommand::MoveAnchor { spring, pos: self.springs[spring].anchor_pos };
+                self.move_anchor(spring, pos);
+                undo
+            }
+            EditorCommand::AddSwitch { index, pos, target } => {
+                self.switches.insert(index, Switch {
+                    pos,
+                    radius: SWITCH_RADIUS,
+                    target_wall_indices: vec![target],
+                    touched: false,
+                    flipped: false,
+                });
+                EditorCommand::RemoveSwitch { index, pos, target }
+            }
+            EditorCommand::RemoveSwitch { index, pos, target } => {
+                self.switches.remove(index);
+                EditorCommand::AddSwitch { index, pos, target }
+            }
+            EditorCommand::AddSpring { index, spring } => {
+                self.springs.insert(index, spring.clone());
+                EditorCommand::RemoveSpring { index, spring }
+            }
+            EditorCommand::RemoveSpring { index, spring } => {
+                self.springs.remove(index);
+                EditorCommand::AddSpring { index, spring }
+            }
+            EditorCommand::AddFlipZone { index, zone } => {
+                self.flip_zones.insert(index, zone);
+                EditorCommand::RemoveFlipZone { index, zone }
+            }
+            EditorCommand::RemoveFlipZone { index, zone } => {
+                self.flip_zones.remove(index);
+                EditorCommand::AddFlipZone { index, zone }
+            }
+            EditorCommand::AddSoftBody { center, radius, points } => {
+                let first = self.objects.len();
+                self.spawn_soft_body(center, radius, points);
+                EditorCommand::RemoveSoftBody { center, radius, points, objects: (first..self.objects.len()).collect() }
+            }
+            EditorCommand::RemoveSoftBody { center, radius, points, mut objects } => {
+                // Highest first, so the indices still to go don't shift. Every spring or
+                // no-collide pair touching a point goes with it
+                objects.sort_unstable_by(|a, b| b.cmp(a));
+                for index in objects {
+                    self.remove_object(index);
                 }
+                EditorCommand::AddSoftBody { center, radius, points }
+            }
+            EditorCommand::Batch(commands) => {
+                let mut inverses: Vec<EditorCommand> = commands.into_iter().map(|c| self.execute(c)).collect();
+                inverses.reverse();
+                EditorCommand::Batch(inverses)
             }
         }
+    }
+
+    // Put an object at `index`, shifting every stored index at or after it
+    fn insert_object(&mut self, index: usize, object: PhysicsObject) {
+        self.objects.insert(index, object);
+        self.shift_object_indices(index, |i| i + 1);
+    }
 
-        // Wall collisions
+    // The one safe way to take an object out: springs and no-collide pairs using it are
+    // dropped and every stored index after it shifts down. The dropped links come back
+    // still numbered for the old layout and tagged with their old list positions, so an
+    // undo can put them back exactly as they were
+    fn remove_object(&mut self, index: usize) -> RemovedLinks {
+        let (springs, kept) = std::mem::take(&mut self.springs)
+            .into_iter()
+            .enumerate()
+            .partition::<Vec<_>, _>(|(_, s)| s.object_index == index || s.anchor == Some(index));
+        self.springs = kept.into_iter().map(|(_, s)| s).collect();
+        let (pairs, kept) = std::mem::take(&mut self.no_collide_pairs)
+            .into_iter()
+            .enumerate()
+            .partition::<Vec<_>, _>(|&(_, (a, b))| a == index || b == index);
+        self.no_collide_pairs = kept.into_iter().map(|(_, p)| p).collect();
+        // A jelly loses the point, or stops being one without its center
+        self.soft_bodies.retain(|b| b.center != index);
+        for body in &mut self.soft_bodies {
+            body.ring.retain(|&i| i != index);
+        }
         for obj in &mut self.objects {
-            if obj.fixed { continue; }
-            
-            for wall in &self.walls {
-                let wall_vec = wall.end - wall.start;
-                let wall_len = wall_vec.length();
-                let wall_dir = wall_vec * (1.0 / wall_len);
-                
-                let to_ball = obj.pos - wall.start;
-                let along_wall = to_ball.dot(&wall_dir);
-                
-                if along_wall >= 0.0 && along_wall <= wall_len {
-                    let normal = Vec2::new(-wall_dir.y, wall_dir.x);
-                    let dist = to_ball.dot(&normal);
-                    
-                    if dist.abs() <= obj.radius {
-                        let penetration = obj.radius - dist.abs();
-                        obj.pos = obj.pos + normal * (penetration * dist.signum());
-                        
-                        let vel_normal = obj.vel.dot(&normal);
-                        if vel_normal * dist < 0.0 {
-                            obj.vel = obj.vel - normal * (vel_normal * (1.0 + obj.bounciness));
-                        }
-                    }
-                }
+            if obj.last_touched_by == Some(index) {
+                obj.last_touched_by = None;
             }
         }
+        self.objects.remove(index);
+        self.shift_object_indices(index + 1, |i| i - 1);
+        (springs, pairs)
     }
 
-    fn render(&self, ui: &mut egui::Ui) {
-        let painter = ui.painter();
-        
-        // draw boundaries
-        painter.rect_stroke(
-            self.canvas_rect,
-            0.0,
-            egui::Stroke::new(4.0, egui::Color32::LIGHT_GRAY),
-            egui::StrokeKind::Inside
-        );
+    fn in_soft_body(&self, index: usize) -> bool {
+        self.soft_bodies.iter().any(|b| b.center == index || b.ring.contains(&index))
+    }
 
-        // Draw springs
-        for spring in &self.springs {
-            if let Some(obj) = self.objects.get(spring.object_index) {
-                let anchor_pos = if let Some(anchor_idx) = spring.anchor {
-                    if let Some(anchor_obj) = self.objects.get(anchor_idx) {
-                        egui::pos2(anchor_obj.pos.x, anchor_obj.pos.y)
-                    } else {
-                        continue;
-                    }
-                } else {
-                    egui::pos2(spring.anchor_pos.x, spring.anchor_pos.y)
-                };
+    // Only safe for objects no spring or soft body refers to
+    fn is_free_standing(&self, index: usize) -> bool {
+        !self.springs.iter().any(|s| s.object_index == index || s.anchor == Some(index))
+            && !self.in_soft_body(index)
+            && !self.no_collide_pairs.iter().any(|&(a, b)| a == index || b == index)
+    }
 
-                let obj_pos = egui::pos2(obj.pos.x, obj.pos.y);
-                let dist = ((obj_pos.x - anchor_pos.x).powi(2) + 
-                           (obj_pos.y - anchor_pos.y).powi(2)).sqrt();
-                let segments = (dist / 10.0).max(4.0) as i32;
-                let dx = (obj_pos.x - anchor_pos.x) / segments as f32;
-                let dy = (obj_pos.y - anchor_pos.y) / segments as f32;
-                
-                let mut points = Vec::new();
-                for i in 0..=segments {
-                    let x = anchor_pos.x + dx * i as f32;
-                    let y = anchor_pos.y + dy * i as f32;
-                    let offset = if i % 2 == 0 { 5.0 } else { -5.0 };
-                    let normal_x = -dy / dist * offset;
-                    let normal_y = dx / dist * offset;
-                    points.push(egui::pos2(x + normal_x, y + normal_y));
-                }
-                
-                for i in 0..points.len()-1 {
-                    painter.line_segment(
-                        [points[i], points[i+1]],
-                        egui::Stroke::new(2.0, egui::Color32::DARK_GRAY),
-                    );
-                }
+    fn shift_object_indices(&mut self, from: usize, shift: impl Fn(usize) -> usize) {
+        let fix = |i: &mut usize| if *i >= from { *i = shift(*i) };
+        for spring in &mut self.springs {
+            fix(&mut spring.object_index);
+            if let Some(anchor) = &mut spring.anchor {
+                fix(anchor);
+            }
+        }
+        for body in &mut self.soft_bodies {
+            fix(&mut body.center);
+            body.ring.iter_mut().for_each(fix);
+        }
+        for (a, b) in &mut self.no_collide_pairs {
+            fix(a);
+            fix(b);
+        }
+        for obj in &mut self.objects {
+            if let Some(player) = &mut obj.last_touched_by {
+                fix(player);
             }
         }
+        self.satisfied_goals.clear();
+        self.winning_hit = None;
+        self.deferred_pairs.clear();
+    }
 
-        // Draw walls
-        for wall in &self.walls {
-            let color = if wall.is_user_placed {
-                egui::Color32::from_rgb(100, 200, 255)
-            } else {
-                egui::Color32::WHITE
-            };
-            
-            painter.line_segment(
-                [egui::pos2(wall.start.x, wall.start.y), egui::pos2(wall.end.x, wall.end.y)],
-                egui::Stroke::new(6.0, color),
-            );
+    // The level's budget, or zero while the no-walls challenge is on
+    fn wall_budget(&self) -> usize {
+        if self.no_wall_challenge { 0 } else { self.max_walls }
+    }
+
+    fn reset_simulation(&mut self) {
+        for obj in &mut self.objects {
+            obj.pos = obj.initial_pos;
+            obj.vel = obj.initial_vel;
+            obj.acc = Vec2::new(0.0, 0.0);
+            obj.bounciness = obj.initial_bounciness;
+            obj.lost = false;
+            obj.gravity_flipped = false;
+            obj.damage = 0.0;
+            obj.last_touched_by = None;
+            obj.angle = 0.0;
+            obj.angular_vel = 0.0;
+            obj.wake();
         }
+        self.game_state = GameState::Planning;
+        self.win_time = None;
+        self.win_armed = false;
+        self.paused = false;
+        self.collision_labels.clear();
+        self.history.clear();
+        self.goal_tension = 0.0;
+        self.last_goal_gap = None;
+        self.satisfied_goals.clear();
+        self.winning_hit = None;
+        self.deferred_pairs.clear();
+        self.reset_switches();
+        self.sim_time = 0.0;
+        self.rest_timer = 0.0;
+        self.nonfinite_resets = 0;
+        self.step_count = 0;
+        self.bonus_score = 0;
+        self.last_medal = None;
+        self.reset_combo();
+        self.resolve_initial_overlaps();
+    }
 
-        // Draw wall preview
-        if let Some(start) = self.placing_wall {
-            if let Some(pointer_pos) = ui.input(|i| i.pointer.hover_pos()) {
-                painter.line_segment(
-                    [egui::pos2(start.x, start.y), pointer_pos],
-                    egui::Stroke::new(6.0, egui::Color32::from_rgba_premultiplied(100, 200, 255, 150)),
-                );
+    // Flip every door back to how the level starts
+    fn reset_switches(&mut self) {
+        for i in 0..self.switches.len() {
+            if self.switches[i].flipped {
+                self.toggle_doors(i);
             }
+            self.switches[i].touched = false;
         }
+    }
+
+    fn toggle_doors(&mut self, switch: usize) {
+        let switch = &mut self.switches[switch];
+        switch.flipped = !switch.flipped;
+        for &index in &switch.target_wall_indices {
+            if let Some(wall) = self.walls.get_mut(index) {
+                wall.active = !wall.active;
+            }
+        }
+        // Balls resting on a door that just opened have to notice
+        self.objects.iter_mut().for_each(PhysicsObject::wake);
+    }
+
+    // Closest designer wall by midpoint, user walls come and go so they can't be doors
+    fn nearest_level_wall(&self, pos: Vec2) -> Option<usize> {
+        (0..self.walls.len())
+            .filter(|&i| !self.walls[i].is_user_placed)
+            .min_by(|&a, &b| {
+                let gap = |i: usize| ((self.walls[i].start + self.walls[i].end) * 0.5).distance_squared(&pos);
+                gap(a).total_cmp(&gap(b))
+            })
+    }
+
+    // A switch fires when a ball first rolls onto it, not on every step it stays there
+    fn update_switches(&mut self) {
+        for i in 0..self.switches.len() {
+            let pad = Circle { center: self.switches[i].pos, radius: self.switches[i].radius };
+            let touched = self.objects.iter().any(|o| !o.fixed && !o.lost && o.circle().overlaps(&pad));
+            if touched && !self.switches[i].touched {
+                self.toggle_doors(i);
+            }
+            self.switches[i].touched = touched;
+        }
+    }
+
+    // Nudge apart balls that start out overlapping, positions only, so the first
+    // step doesn't fire them apart with a big impulse. Pairs the level lets pass
+    // through each other are meant to overlap and stay put
+    fn resolve_initial_overlaps(&mut self) {
+        const MAX_PASSES: usize = 20;
+        let mut moved_objects = HashSet::new();
+        for _ in 0..MAX_PASSES {
+            let mut moved = false;
+            for j in 1..self.objects.len() {
+                for i in 0..j {
+                    if self.no_collide_pairs.contains(&(i, j)) || self.no_collide_pairs.contains(&(j, i)) {
+                        continue;
+                    }
+                    let (left, right) = self.objects.split_at_mut(j);
+                    let (a, b) = (&mut left[i], &mut right[0]);
+                    if a.lost || b.lost { continue; }
+
+                    let delta = b.pos - a.pos;
+                    let dist = delta.length();
+                    let overlap = a.radius + b.radius - dist;
+                    if overlap <= 0.01 { continue; }
+
+                    // Dead-center spawns have no direction, pick one
+                    let normal = if dist > 0.0 { delta / dist } else { Vec2::new(1.0, 0.0) };
+                    // Fixed and kinematic objects have no inverse mass and never move
+                    let (inv_a, inv_b) = (a.inv_mass(), b.inv_mass());
+                    if inv_a + inv_b <= 0.0 { continue; }
+                    let push = normal * (overlap / (inv_a + inv_b));
+                    if inv_a > 0.0 {
+                        a.pos -= push * inv_a;
+                        moved_objects.insert(i);
+                    }
+                    if inv_b > 0.0 {
+                        b.pos += push * inv_b;
+                        moved_objects.insert(j);
+                    }
+                    moved = true;
+                }
+            }
+            if !moved { break; }
+        }
+        // Retries start from the separated layout too
+        for i in moved_objects {
+            self.objects[i].initial_pos = self.objects[i].pos;
+        }
+    }
+
+    // Grade the winning run's time and keep the best medal for this level
+    fn award_medal(&mut self) {
+        let Some(times) = self.medal_times else { return };
+        let medal = [Medal::Gold, Medal::Silver, Medal::Bronze].into_iter()
+            .zip(times)
+            .find(|&(_, limit)| self.sim_time <= limit)
+            .map(|(medal, _)| medal);
+        self.last_medal = medal;
+        if let Some(medal) = medal {
+            let best = self.best_medals.entry(self.level).or_insert(medal);
+            *best = (*best).max(medal);
+        }
+    }
+
+    // Step back one recorded frame and pause there; resuming continues from it
+    fn rewind(&mut self) {
+        self.paused = true;
+        if let Some(snapshot) = self.history.pop_back() {
+            self.restore(snapshot);
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            time: self.sim_time,
+            steps: self.step_count,
+            win_armed: self.win_armed,
+            objects: self.objects.clone(),
+            springs: self.springs.clone(),
+            satisfied_goals: self.satisfied_goals.clone(),
+            switches: self.switches.clone(),
+            walls_active: self.walls.iter().map(|w| w.active).collect(),
+        }
+    }
+
+    // Jump back to a snapshot, dropping anything recorded after it
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.objects = snapshot.objects;
+        self.springs = snapshot.springs;
+        self.satisfied_goals = snapshot.satisfied_goals;
+        self.switches = snapshot.switches;
+        for (wall, active) in self.walls.iter_mut().zip(snapshot.walls_active) {
+            wall.active = active;
+        }
+        self.sim_time = snapshot.time;
+        self.step_count = snapshot.steps;
+        self.win_armed = snapshot.win_armed;
+        let time = self.sim_time;
+        self.trajectory.retain(|s| s.time < time);
+        self.history.retain(|s| s.time < time);
+    }
+
+    fn spawn_label(&mut self, pos: Vec2, text: String) {
+        self.collision_labels.push(CollisionLabel {
+            pos,
+            text,
+            life: COLLISION_LABEL_LIFETIME,
+        });
+    }
+
+    fn register_combo_hit(&mut self, pos: Vec2) {
+        if self.combo_count > 0 && self.sim_time - self.last_combo_hit <= COMBO_WINDOW {
+            self.combo_count += 1;
+        } else {
+            self.combo_count = 1;
+        }
+        self.last_combo_hit = self.sim_time;
+
+        if self.combo_count >= 2 {
+            self.bonus_score += self.combo_count * COMBO_POINTS;
+            self.spawn_label(pos, format!("Combo x{}!", self.combo_count));
+        }
+    }
+
+    fn reset_combo(&mut self) {
+        self.combo_count = 0;
+        self.last_combo_hit = 0.0;
+    }
+
+    fn save_game(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.save_state()).map_err(|e| e.to_string())?;
+        std::fs::write(SAVEGAME_PATH, json).map_err(|e| e.to_string())
+    }
+
+    fn load_game(&mut self) -> Result<(), String> {
+        let json = std::fs::read_to_string(SAVEGAME_PATH).map_err(|e| e.to_string())?;
+        let save: SaveGame = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        self.restore_save(save);
+        Ok(())
+    }
+
+    fn save_state(&self) -> SaveGame {
+        SaveGame {
+            level: self.level,
+            game_state: self.game_state,
+            walls: self.walls.clone(),
+            switches: self.switches.clone(),
+            objects: self.objects.iter().map(|o| ObjectState {
+                pos: o.pos,
+                vel: o.vel,
+                initial_pos: o.initial_pos,
+                initial_vel: o.initial_vel,
+                radius: o.radius,
+                mass: o.mass,
+                color: o.color.to_array(),
+                bounciness: o.bounciness,
+                initial_bounciness: o.initial_bounciness,
+                plasticity: o.plasticity,
+                is_player: o.is_player,
+                is_goal: o.is_goal,
+                fixed: o.fixed,
+                repulsive: o.repulsive,
+                planet: o.planet,
+                lost: o.lost,
+                gravity_flipped: o.gravity_flipped,
+                angle: o.angle,
+                angular_vel: o.angular_vel,
+                kinematic: o.kinematic.clone(),
+                health: o.health,
+                damage: o.damage,
+                last_touched_by: o.last_touched_by,
+                spawn_id: o.spawn_id,
+            }).collect(),
+            springs: self.springs.clone(),
+            no_collide_pairs: self.no_collide_pairs.clone(),
+            soft_bodies: self.soft_bodies.iter().map(|b| (b.center, b.ring.clone())).collect(),
+            endless_seed: self.endless_seed,
+            level_stats: self.level_stats.clone(),
+            best_medals: self.best_medals.clone(),
+            best_steps: self.best_steps.clone(),
+            no_wall_wins: self.no_wall_wins,
+            bonus_score: self.bonus_score,
+        }
+    }
+
+    fn restore_save(&mut self, save: SaveGame) {
+        // Endless levels are generated from the seed, so it has to be back first. The
+        // jitter is skipped since everything it would move comes from the save
+        self.endless_seed = save.endless_seed;
+        self.level = save.level;
+        let start_jitter = std::mem::replace(&mut self.start_jitter, false);
+        self.setup_level(save.level);
+        self.start_jitter = start_jitter;
+
+        // The saved scene replaces the level's wholesale, sandbox and editor changes included
+        self.walls = save.walls;
+        self.switches = save.switches;
+        self.objects = save.objects.into_iter().map(|state| {
+            let [r, g, b, a] = state.color;
+            PhysicsObject {
+                vel: state.vel,
+                initial_pos: state.initial_pos,
+                initial_vel: state.initial_vel,
+                mass: state.mass,
+                color: egui::Color32::from_rgba_premultiplied(r, g, b, a),
+                bounciness: state.bounciness,
+                initial_bounciness: state.initial_bounciness,
+                plasticity: state.plasticity,
+                is_player: state.is_player,
+                is_goal: state.is_goal,
+                fixed: state.fixed,
+                repulsive: state.repulsive,
+                planet: state.planet,
+                lost: state.lost,
+                gravity_flipped: state.gravity_flipped,
+                angle: state.angle,
+                angular_vel: state.angular_vel,
+                kinematic: state.kinematic,
+                health: state.health,
+                damage: state.damage,
+                last_touched_by: state.last_touched_by,
+                spawn_id: state.spawn_id,
+                ..PhysicsObject::ball(state.pos, state.radius, DEFAULT_DENSITY)
+            }
+        }).collect();
+        self.springs = save.springs;
+        self.no_collide_pairs = save.no_collide_pairs;
+        let count = self.objects.len();
+        self.soft_bodies = save.soft_bodies.into_iter()
+            .filter(|(center, ring)| *center < count && ring.iter().all(|&i| i < count))
+            .map(|(center, ring)| SoftBody { center, ring, color: self.objects[center].color })
+            .collect();
+        self.next_spawn_id = self.objects.iter().filter_map(|o| o.spawn_id).max().map_or(0, |id| id + 1);
+        self.apply_player_texture();
+
+        self.level_stats = save.level_stats;
+        self.best_medals = save.best_medals;
+        self.best_steps = save.best_steps;
+        self.no_wall_wins = save.no_wall_wins;
+        self.bonus_score = save.bonus_score;
+
+        // The clock isn't saved, so a won level restarts its advance delay
+        self.game_state = save.game_state;
+        if matches!(self.game_state, GameState::Won) {
+            self.win_time = Some(self.clock);
+        }
+        self.last_time = Instant::now();
+    }
+
+    // Nothing that could still move is moving
+    fn all_at_rest(&self) -> bool {
+        self.objects.iter()
+            .filter(|o| !o.fixed && !o.lost)
+            .all(|o| o.sleeping || o.vel.length() < SLEEP_SPEED)
+    }
+
+    // The level is unwinnable once a goal, or every ball that could hit it, is lost
+    fn lost_needed_ball(&self) -> bool {
+        let goal_lost = self.objects.iter().any(|o| o.is_goal && o.lost);
+        let mut hitters = self.objects.iter().filter(|o| !o.is_goal && !o.is_player && !o.fixed).peekable();
+        let hitters_lost = hitters.peek().is_some() && hitters.all(|o| o.lost);
+        goal_lost || hitters_lost
+    }
+
+    // Run the level headlessly with `walls` as the user walls and report whether it wins
+    fn wins_with(&self, walls: &[Wall]) -> bool {
+        let mut sim = self.clone();
+        sim.walls.retain(|w| !w.is_user_placed);
+        sim.walls.extend(walls.iter().cloned());
+        sim.record_trajectory = false;
+        sim.record_history = false;
+        // Trial runs are silent
+        sim.collision_observer = None;
+        sim.reset_simulation();
+        sim.game_state = GameState::Simulating;
+
+        let mut time = 0.0;
+        while time < SOLVER_MAX_TIME {
+            sim.update_physics(SOLVER_DT);
+            match sim.game_state {
+                GameState::Won => return true,
+                GameState::Planning => return false, // attempt was failed mid-run
+                GameState::Simulating => {}
+            }
+            time += SOLVER_DT;
+        }
+        false
+    }
+
+    fn random_user_wall(&self, rng: &mut StdRng) -> Wall {
+        let start = Vec2::new(
+            rng.random_range(BOARDER_START..BOARDER_START + self.bounds.0),
+            rng.random_range(15.0..self.bounds.1),
+        );
+        let angle = rng.random_range(0.0..std::f32::consts::TAU);
+        let length = rng.random_range(60.0..200.0);
+        Wall {
+            start,
+            end: start + Vec2::from_angle(angle) * length,
+            is_user_placed: true,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
+        }
+    }
+
+    // Place the replay's walls, launch the player at its velocity and report whether it wins
+    fn replay_wins(&self, replay: &Replay) -> bool {
+        let mut sim = self.clone();
+        for player in sim.objects.iter_mut().filter(|o| o.is_player) {
+            player.initial_vel = replay.player_vel;
+        }
+        sim.wins_with(&replay.user_walls())
+    }
+
+    // Place the replay's walls and launch it live, for the attract demo
+    fn start_replay(&mut self, replay: &Replay) {
+        for player in self.objects.iter_mut().filter(|o| o.is_player) {
+            player.initial_vel = replay.player_vel;
+        }
+        self.walls.retain(|w| !w.is_user_placed);
+        self.walls.extend(replay.user_walls());
+        self.record_trajectory = false;
+        self.reset_simulation();
+        self.game_state = GameState::Simulating;
+    }
+
+    // Fresh level 1 playing its reference solution
+    fn attract_demo() -> Option<Box<PhysicsApp>> {
+        let mut demo = PhysicsApp::default();
+        let replay = demo.reference_solution.clone()?;
+        demo.start_replay(&replay);
+        Some(Box::new(demo))
+    }
+
+    // Brute-force search for user walls within the budget that win the level, giving up
+    // once `deadline` passes
+    fn find_solution(&self, rng: &mut StdRng, deadline: Option<Instant>) -> Option<Vec<Wall>> {
+        if self.wins_with(&[]) {
+            return Some(Vec::new());
+        }
+        if self.wall_budget() == 0 {
+            return None;
+        }
+        for _ in 0..SOLVER_ATTEMPTS {
+            if deadline.is_some_and(|d| Instant::now() > d) {
+                return None;
+            }
+            let count = rng.random_range(1..=self.wall_budget());
+            let walls: Vec<Wall> = (0..count).map(|_| self.random_user_wall(rng)).collect();
+            if self.wins_with(&walls) {
+                return Some(walls);
+            }
+        }
+        None
+    }
+
+    // Show the next of the level's cached solutions, searching for them on first use.
+    // The search is seeded by the level so the same hints come up in the same order
+    fn next_hint(&mut self) {
+        if !self.hint_solutions.contains_key(&self.level) {
+            let mut rng = StdRng::seed_from_u64(self.level_seed(self.level));
+            let mut solutions: Vec<Vec<Wall>> = Vec::new();
+            for _ in 0..HINT_SOLUTIONS {
+                match self.find_solution(&mut rng, None) {
+                    // Nothing else to cycle to once no walls are needed at all
+                    Some(walls) if walls.is_empty() => {
+                        solutions = vec![walls];
+                        break;
+                    }
+                    Some(walls) => solutions.push(walls),
+                    None => {}
+                }
+            }
+            self.hint_solutions.insert(self.level, solutions);
+        }
+        let count = self.hint_solutions[&self.level].len();
+        if count == 0 {
+            self.hint_shown = None;
+            self.status_message = Some("No hint found for this level".to_string());
+            return;
+        }
+        let index = self.hint_shown.map_or(0, |i| (i + 1) % count);
+        self.hint_shown = Some(index);
+        self.status_message = Some(if self.hint_solutions[&self.level][index].is_empty() {
+            "Hint: no walls needed, just launch".to_string()
+        } else {
+            format!("Hint {} of {count}", index + 1)
+        });
+    }
+
+    // Write the recorded player trajectory as CSV and report the outcome on screen
+    fn finish_trajectory(&mut self) {
+        let mut csv = String::from("time,x,y,vx,vy,speed\n");
+        for sample in &self.trajectory {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                sample.time, sample.pos.x, sample.pos.y, sample.vel.x, sample.vel.y, sample.vel.length()
+            ));
+        }
+        self.status_message = Some(match std::fs::write(TRAJECTORY_PATH, csv) {
+            Ok(()) => format!("Wrote {} samples to {TRAJECTORY_PATH}", self.trajectory.len()),
+            Err(e) => format!("Trajectory export failed: {e}"),
+        });
+        self.trajectory.clear();
+    }
+
+    // The level's starting layout as an SVG in canvas coordinates, drawn like `render`
+    fn scene_svg(&self) -> String {
+        let hex = |c: egui::Color32| format!("#{:02x}{:02x}{:02x}", c.r(), c.g(), c.b());
+        let rect = self.canvas_rect;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+            rect.min.x, rect.min.y, rect.width(), rect.height(), rect.width(), rect.height()
+        );
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"#1b1b1b\" stroke=\"{}\" stroke-width=\"4\"/>\n",
+            rect.min.x, rect.min.y, rect.width(), rect.height(), hex(egui::Color32::LIGHT_GRAY)
+        ));
+
+        for wall in &self.walls {
+            let color = if wall.is_user_placed { egui::Color32::from_rgb(100, 200, 255) } else { egui::Color32::WHITE };
+            svg.push_str(&format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\"/>\n",
+                wall.start.x, wall.start.y, wall.end.x, wall.end.y, hex(color), wall.thickness
+            ));
+        }
+
+        for spring in &self.springs {
+            let Some(obj) = self.objects.get(spring.object_index) else { continue };
+            let anchor = match spring.anchor {
+                Some(index) => match self.objects.get(index) {
+                    Some(anchor_obj) => anchor_obj.initial_pos,
+                    None => continue,
+                },
+                None => spring.anchor_pos,
+            };
+            let points: Vec<String> = spring_zigzag(anchor, obj.initial_pos).iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect();
+            svg.push_str(&format!(
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+                points.join(" "), hex(egui::Color32::DARK_GRAY)
+            ));
+        }
+
+        for obj in &self.objects {
+            let (x, y) = (obj.initial_pos.x, obj.initial_pos.y);
+            svg.push_str(&format!("<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"{}\"/>\n", obj.radius, hex(obj.color)));
+            let outline = if obj.is_player {
+                Some(egui::Color32::WHITE)
+            } else if obj.fixed {
+                Some(egui::Color32::from_rgb(60, 60, 60))
+            } else {
+                None
+            };
+            if let Some(outline) = outline {
+                svg.push_str(&format!(
+                    "<circle cx=\"{x}\" cy=\"{y}\" r=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"3\"/>\n",
+                    obj.radius, hex(outline)
+                ));
+            }
+            if obj.is_goal {
+                for (p1, p2) in goal_star(obj.initial_pos) {
+                    svg.push_str(&format!(
+                        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#ffffff\" stroke-width=\"2\"/>\n",
+                        p1.x, p1.y, p2.x, p2.y
+                    ));
+                }
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    // The plunger pushes along the level's launch direction, or right if the player starts still
+    fn plunger_axis(player: &PhysicsObject) -> Vec2 {
+        if player.initial_vel.length() > 0.0 { player.initial_vel.normalized() } else { Vec2::new(1.0, 0.0) }
+    }
+
+    // Holding pulls the plunger back, letting go fires the player and launches the attempt
+    fn update_plunger(&mut self, held: bool, dt: f32) {
+        if held {
+            self.plunger_pull = (self.plunger_pull + PLUNGER_PULL_SPEED * dt).min(PLUNGER_MAX_PULL);
+            return;
+        }
+        if self.plunger_pull <= 0.0 {
+            return;
+        }
+        let speed = self.plunger_pull * PLUNGER_STRENGTH;
+        self.plunger_pull = 0.0;
+        if let Some(player) = self.objects.iter_mut().find(|o| o.is_player) {
+            player.initial_vel = Self::plunger_axis(player) * speed;
+            player.vel = player.initial_vel;
+            self.launch();
+        }
+    }
+
+    // Path the player will take, following gravity and bouncing off walls. Other balls
+    // aren't simulated, so the path stops where it first reaches one
+    fn predict_trajectory(&self) -> Prediction {
+        let Some(player_index) = self.objects.iter().position(|o| o.is_player) else {
+            return Prediction { path: Vec::new(), first_contact: None };
+        };
+        let player = &self.objects[player_index];
+        // Balls the player passes straight through can't be its first contact
+        let ignored = |i: usize| self.no_collide_pairs.contains(&(player_index, i)) || self.no_collide_pairs.contains(&(i, player_index));
+        let area = self.play_area();
+        let (mut pos, mut vel) = (player.pos, player.vel);
+        let mut points = vec![pos];
+        let mut bounces = 0;
+        let mut time = 0.0;
+        while time < PREDICTION_TIME {
+            vel += self.gravity * SOLVER_DT;
+            pos += vel * SOLVER_DT;
+            time += SOLVER_DT;
+            points.push(pos);
+            if !area.contains_point(pos) {
+                break;
+            }
+            let ghost = Circle { center: pos, radius: player.radius };
+            let hit = (0..self.objects.len()).find(|&i| {
+                let obj = &self.objects[i];
+                !obj.is_player && !obj.lost && !ignored(i) && obj.circle().overlaps(&ghost)
+            });
+            if hit.is_some() {
+                return Prediction { path: points, first_contact: hit };
+            }
+            for wall in self.walls.iter().filter(|w| w.active) {
+                if let Some((normal, dist)) = wall.contact(pos, player.radius) {
+                    let vel_normal = vel.dot(&normal);
+                    if vel_normal * dist < 0.0 {
+                        let restitution = floored_restitution(
+                            vel_normal,
+                            wall.restitution.unwrap_or(player.bounciness),
+                            self.restitution_floor,
+                        );
+                        vel = vel.reflect(&normal) + normal * (vel_normal * (1.0 - restitution));
+                        vel *= 1.0 - self.contact_damping;
+                        bounces += 1;
+                    }
+                }
+            }
+            if bounces >= PREDICTION_BOUNCES {
+                break;
+            }
+        }
+        Prediction { path: points, first_contact: None }
+    }
+
+    // Slingshot aim: the player launches away from the mouse, faster the further it's pulled
+    fn aim_player(&mut self, mouse_pos: Vec2) {
+        let lock = self.lock_aim_direction;
+        let Some(player) = self.objects.iter_mut().find(|o| o.is_player) else { return };
+        // Nothing changes until the drag leaves the ball, so a plain click keeps the shot
+        if player.pos.distance(&mouse_pos) <= player.radius {
+            return;
+        }
+        let pull = (player.pos - mouse_pos) * AIM_STRENGTH;
+        let vel = if lock {
+            let axis = Self::plunger_axis(player);
+            axis * pull.dot(&axis).clamp(AIM_MIN_SPEED, AIM_MAX_SPEED)
+        } else if pull.length() > AIM_MAX_SPEED {
+            pull.normalized() * AIM_MAX_SPEED
+        } else {
+            pull
+        };
+        player.initial_vel = vel;
+        player.vel = vel;
+    }
+
+    // Start an attempt from the planning phase
+    fn launch(&mut self) {
+        self.game_state = GameState::Simulating;
+        self.trajectory.clear();
+        self.level_stats.entry(self.level).or_default().attempts += 1;
+    }
+
+    // Plain-text version of the summary table, for sharing
+    fn results_summary(&self) -> String {
+        let mut text = String::from("Physics puzzle results\n");
+        let (mut total_walls, mut total_time) = (0, 0.0);
+        for level in 1..=LEVEL_COUNT {
+            let stats = self.level_stats.get(&level).cloned().unwrap_or_default();
+            let medal = self.best_medals.get(&level).map_or("-", |m| m.name());
+            text.push_str(&format!(
+                "Level {level}: {} attempts, {} walls, {:.2}s, {medal}\n",
+                stats.attempts, stats.walls_used, stats.win_time
+            ));
+            total_walls += stats.walls_used;
+            total_time += stats.win_time;
+        }
+        text.push_str(&format!("Total: {total_walls} walls, {total_time:.2}s\n"));
+        text
+    }
+
+    // Object from the palette, or a ball at rest built from `next_ball`, dropped from the
+    // editor or the console. At the object limit it reuses the oldest dropped ball's slot
+    // instead of growing
+    fn spawn_ball(&mut self, pos: Vec2) -> Result<(), &'static str> {
+        let mut object = match self.palette {
+            Some(kind) => make_archetype(kind, pos),
+            None => {
+                let template = self.next_ball;
+                let mut object = PhysicsObject::ball(pos, template.radius, template.density.unwrap_or(DEFAULT_DENSITY));
+                if template.density.is_none() {
+                    object.mass = template.mass;
+                }
+                object.bounciness = template.bounciness;
+                object.initial_bounciness = template.bounciness;
+                object
+            }
+        };
+        object.spawn_id = Some(self.next_spawn_id);
+        self.next_spawn_id += 1;
+
+        let (index, add) = if self.objects.len() < self.max_objects {
+            let index = self.objects.len();
+            (index, EditorCommand::AddObject { index, object })
+        } else {
+            let index = self.oldest_spawned().ok_or("Ball limit reached")?;
+            (index, EditorCommand::ReplaceObject { index, object })
+        };
+        if self.palette == Some(Archetype::Pendulum) {
+            let spring = Spring {
+                object_index: index,
+                anchor: None,
+                anchor_pos: pos - Vec2::new(0.0, PENDULUM_LENGTH),
+                rest_length: PENDULUM_LENGTH,
+                stiffness: PENDULUM_STIFFNESS,
+            };
+            self.edit(EditorCommand::Batch(vec![add, EditorCommand::AddSpring { index: self.springs.len(), spring }]));
+        } else {
+            self.edit(add);
+        }
+        Ok(())
+    }
+
+    // Dropped ball that has been around longest. Only free-standing ones qualify, so
+    // reusing the slot never leaves a spring or jelly pointing at a different ball
+    fn oldest_spawned(&self) -> Option<usize> {
+        (0..self.objects.len())
+            .filter(|&i| self.objects[i].spawn_id.is_some() && !self.objects[i].fixed && self.is_free_standing(i))
+            .min_by_key(|&i| self.objects[i].spawn_id)
+    }
+
+    fn apply_preset(&mut self, preset: Preset) {
+        self.preset = preset;
+        let (min_substeps, solver_iterations, stack_passes, restitution_floor) = match preset {
+            Preset::Fast => (1, 1, 1, 30.0),
+            Preset::Balanced => (1, 1, STACK_SOLVER_PASSES, 20.0),
+            Preset::Accurate => (4, 4, 8, 10.0),
+        };
+        self.min_substeps = min_substeps;
+        self.solver_iterations = solver_iterations;
+        self.stack_passes = stack_passes;
+        self.restitution_floor = restitution_floor;
+    }
+
+    // Dispatch one console line, returning the reply to print
+    fn run_command(&mut self, line: &str) -> Result<String, String> {
+        let number = |word: &str| word.parse::<f32>().map_err(|_| format!("Not a number: {word}"));
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["spawn", "ball", x, y] => {
+                let pos = Vec2::new(number(x)?, number(y)?);
+                self.spawn_ball(pos)?;
+                Ok(format!("Spawned ball at {} {}", pos.x, pos.y))
+            }
+            ["gravity", x, y] => {
+                self.gravity = Vec2::new(number(x)?, number(y)?);
+                Ok(format!("Gravity set to {} {}", self.gravity.x, self.gravity.y))
+            }
+            ["goto", "level", n] => {
+                let level: u32 = n.parse().map_err(|_| format!("Not a level: {n}"))?;
+                if level == 0 || (level > LEVEL_COUNT && !self.endless_mode) {
+                    return Err(format!("No level {level}"));
+                }
+                self.change_level(level);
+                Ok(format!("Loading level {level}"))
+            }
+            ["clearwalls"] => {
+                self.clear_user_walls();
+                Ok("User walls cleared".to_string())
+            }
+            ["speed", k] => {
+                self.time_scale = number(k)?.clamp(0.05, 4.0);
+                Ok(format!("Speed set to {}", self.time_scale))
+            }
+            ["help"] => Ok("spawn ball x y | gravity x y | goto level n | clearwalls | speed k".to_string()),
+            [] => Ok(String::new()),
+            _ => Err(format!("Unknown command: {line} (try `help`)")),
+        }
+    }
+
+    fn show_console(&mut self, ctx: &egui::Context) {
+        let mut open = self.console_open;
+        egui::Window::new("Console").open(&mut open).default_width(360.0).show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(200.0).stick_to_bottom(true).show(ui, |ui| {
+                for line in &self.console_log {
+                    ui.monospace(line);
+                }
+            });
+            let response = ui.add(egui::TextEdit::singleline(&mut self.console_input).desired_width(f32::INFINITY));
+            response.request_focus();
+
+            if response.has_focus() && ui.input(|i| i.key_pressed(egui::Key::ArrowUp))
+                && self.console_history_pos > 0 {
+                self.console_history_pos -= 1;
+                self.console_input = self.console_history[self.console_history_pos].clone();
+            }
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let line = self.console_input.replace('`', "").trim().to_string();
+                self.console_input.clear();
+                self.console_log.push(format!("> {line}"));
+                match self.run_command(&line) {
+                    Ok(reply) if reply.is_empty() => {}
+                    Ok(reply) => self.console_log.push(reply),
+                    Err(error) => self.console_log.push(format!("error: {error}")),
+                }
+                if !line.is_empty() {
+                    self.console_history.push(line);
+                }
+                self.console_history_pos = self.console_history.len();
+            }
+        });
+        self.console_open = open;
+        self.console_input.retain(|c| c != '`');
+    }
+
+    // Logged hits to pick from, and the picked one worked through with its real numbers
+    fn show_lesson(&mut self, ctx: &egui::Context) {
+        let mut open = self.lesson_mode;
+        egui::Window::new("Physics lesson").open(&mut open).default_width(420.0).show(ctx, |ui| {
+            if self.lesson_log.is_empty() {
+                ui.label("Launch a shot, the game pauses on each ball-ball hit");
+                return;
+            }
+            egui::ScrollArea::vertical().max_height(120.0).show(ui, |ui| {
+                for (i, record) in self.lesson_log.iter().enumerate() {
+                    let text = format!("t={:.2}s  ball #{} hits ball #{}  J={:.0}", record.time, record.a, record.b, record.impulse);
+                    ui.selectable_value(&mut self.lesson_selected, Some(i), text);
+                }
+            });
+            ui.separator();
+            let Some(record) = self.lesson_selected.and_then(|i| self.lesson_log.get(i)) else {
+                return;
+            };
+            let (m1, m2) = record.masses;
+            let n = record.normal;
+            let (u1, u2) = (record.before.0.dot(&n), record.before.1.dot(&n));
+            let (v1, v2) = (record.after.0.dot(&n), record.after.1.dot(&n));
+            let e = record.restitution;
+            let mass_text = |m: f32, fixed: bool| if fixed { "∞ (fixed)".to_string() } else { format!("{m:.2}") };
+            ui.monospace(format!("m1 = {}   m2 = {}", mass_text(m1, record.fixed.0), mass_text(m2, record.fixed.1)));
+            ui.monospace(format!("normal n = ({:.2}, {:.2})", n.x, n.y));
+            ui.monospace(format!("before: u1·n = {u1:.1}   u2·n = {u2:.1} px/s"));
+            ui.monospace(format!("after:  v1·n = {v1:.1}   v2·n = {v2:.1} px/s"));
+            ui.add_space(6.0);
+            ui.label("Impulse along the normal:");
+            ui.monospace("J = -(1 + e)(u2 - u1)·n / (1/m1 + 1/m2)");
+            ui.monospace(format!(
+                "  = -(1 + {e:.2})({:.1}) / ({} + {}) = {:.1}",
+                u2 - u1,
+                if record.fixed.0 { "0".to_string() } else { format!("{:.3}", 1.0 / m1) },
+                if record.fixed.1 { "0".to_string() } else { format!("{:.3}", 1.0 / m2) },
+                record.impulse,
+            ));
+            ui.add_space(6.0);
+            ui.label("Restitution, how much closing speed comes back:");
+            ui.monospace(format!("(v2 - v1)·n = -e (u2 - u1)·n  →  {:.1} = {:.1}", v2 - v1, -e * (u2 - u1)));
+            if !record.fixed.0 && !record.fixed.1 {
+                ui.add_space(6.0);
+                ui.label("Momentum along the normal is conserved:");
+                ui.monospace(format!("m1 u1 + m2 u2 = {:.1}", m1 * u1 + m2 * u2));
+                ui.monospace(format!("m1 v1 + m2 v2 = {:.1}", m1 * v1 + m2 * v2));
+                let kinetic = |a: Vec2, b: Vec2| 0.5 * m1 * a.dot(&a) + 0.5 * m2 * b.dot(&b);
+                ui.monospace(format!(
+                    "kinetic energy {:.0} → {:.0}",
+                    kinetic(record.before.0, record.before.1),
+                    kinetic(record.after.0, record.after.1),
+                ));
+            }
+        });
+        self.lesson_mode = open;
+    }
+
+    // Advance one frame while paused, for inspecting a shot step by step
+    fn step_frame(&mut self) {
+        self.paused = false;
+        self.update_physics(SOLVER_DT);
+        self.paused = true;
+    }
+
+    // Live play steps in the solver's fixed SOLVER_DT, so a shot plays out the same on
+    // any refresh rate as it does in the solver and level checks
+    fn advance_physics(&mut self, elapsed: f32) {
+        self.physics_accumulator += elapsed;
+        while self.physics_accumulator >= SOLVER_DT {
+            self.update_physics(SOLVER_DT);
+            self.physics_accumulator -= SOLVER_DT;
+        }
+    }
+
+    fn update_physics(&mut self, dt: f32) {
+        if self.paused {
+            return;
+        }
+        self.clock += dt;
+
+        for label in &mut self.collision_labels {
+            label.life -= dt;
+        }
+        self.collision_labels.retain(|l| l.life > 0.0);
+
+        if self.record_history && matches!(self.game_state, GameState::Simulating) {
+            let snapshot = self.snapshot();
+            self.history.push_back(snapshot);
+            while self.history.front().is_some_and(|s| self.sim_time - s.time > REWIND_SECONDS) {
+                self.history.pop_front();
+            }
+        }
+
+        let substeps = self.substeps.max(self.min_substeps).max(1);
+        let sub_dt = dt / substeps as f32;
+        for _ in 0..substeps {
+            // Stop as soon as a substep wins (or breaks) so the frame behaves like a single step,
+            // unless the scene is meant to play on behind the win
+            let running = match self.game_state {
+                GameState::Simulating => true,
+                GameState::Won => !self.freeze_on_win,
+                GameState::Planning => false,
+            };
+            if !running || self.paused {
+                return;
+            }
+            self.step(sub_dt);
+        }
+    }
+
+    pub(crate) fn step(&mut self, dt: f32) {
+        let was_won = matches!(self.game_state, GameState::Won);
+
+        // Apply spring forces
+        let spring_forces: Vec<(usize, Vec2)> = self.springs.iter().filter_map(|spring| {
+            let obj = self.objects.get(spring.object_index)?;
+            
+            let anchor_pos = if let Some(anchor_idx) = spring.anchor {
+                self.objects.get(anchor_idx)?.pos
+            } else {
+                spring.anchor_pos
+            };
+
+            let to_anchor = anchor_pos - obj.pos;
+            let distance = to_anchor.length();
+            if distance == 0.0 { return None; }
+
+            let direction = to_anchor / distance;
+            let stretch = distance - spring.rest_length;
+            let spring_force = direction * (stretch * spring.stiffness);
+
+            Some((spring.object_index, spring_force))
+        }).collect();
+
+        for (idx, force) in spring_forces {
+            if let Some(obj) = self.objects.get_mut(idx) && !obj.fixed {
+                obj.acc += force * obj.inv_mass();
+            }
+        }
+
+        // Repulsive objects push approaching balls away before they touch
+        let repellers: Vec<(usize, Vec2, f32)> = self.objects.iter().enumerate()
+            .filter(|(_, o)| o.repulsive && !o.lost)
+            .map(|(i, o)| (i, o.pos, o.radius))
+            .collect();
+        for_each_object(&mut self.objects, self.parallel_step, |i, obj| {
+            if obj.fixed || obj.lost { return; }
+            for &(repeller_idx, repeller_pos, repeller_radius) in &repellers {
+                if repeller_idx == i { continue; }
+                let away = obj.pos - repeller_pos;
+                let gap = away.length() - obj.radius - repeller_radius;
+                if gap < REPULSION_RANGE {
+                    let falloff = 1.0 - gap.max(0.0) / REPULSION_RANGE;
+                    obj.acc += away.normalized() * (REPULSION_STRENGTH * falloff);
+                }
+            }
+        });
+
+        // Planet gravity: a = G * m_planet * d / (r² + ε²)^(3/2)
+        let uniform_gravity = if self.orbital_mode {
+            const GRAVITATIONAL_CONSTANT: f32 = 5.0e5;
+            const SOFTENING: f32 = 20.0; // keeps the pull finite at close range
+
+            let planets: Vec<(Vec2, f32)> = self.objects.iter()
+                .filter(|o| o.planet && !o.lost)
+                .map(|o| (o.pos, o.mass))
+                .collect();
+
+            for_each_object(&mut self.objects, self.parallel_step, |_, obj| {
+                if obj.fixed || obj.lost { return; }
+                for &(planet_pos, planet_mass) in &planets {
+                    let to_planet = planet_pos - obj.pos;
+                    let dist_sq = to_planet.dot(&to_planet) + SOFTENING * SOFTENING;
+                    let strength = GRAVITATIONAL_CONSTANT * planet_mass / (dist_sq * dist_sq.sqrt());
+                    obj.acc += to_planet * strength;
+                }
+            });
+            Vec2::new(0.0, 0.0)
+        } else {
+            self.gravity
+        };
+
+        // Update physics for all objects
+        let gravity_zones = &self.gravity_zones;
+        let flip_zones = &self.flip_zones;
+        let drag_coefficient = self.drag_coefficient;
+        let max_speed = self.max_speed;
+        let path_time = self.sim_time + dt;
+        for_each_object(&mut self.objects, self.parallel_step, |_, obj| {
+            // Sleeping objects stay put unless something other than gravity pushes them
+            if obj.sleeping {
+                if obj.acc.length() > WAKE_ACCEL {
+                    obj.wake();
+                } else {
+                    obj.acc = Vec2::new(0.0, 0.0);
+                }
+            }
+
+            if !obj.fixed && !obj.lost && !obj.sleeping {
+                // The zone containing the center wins, otherwise the global gravity
+                let mut gravity = gravity_zones.iter()
+                    .find(|z| z.rect.contains_point(obj.pos))
+                    .map_or(uniform_gravity, |z| z.gravity);
+                if obj.gravity_flipped {
+                    gravity *= -1.0;
+                }
+                // Air drag scales with cross-section: F = -0.5 * Cd * r * |v| * v
+                let drag = obj.vel * (-0.5 * drag_coefficient * obj.radius * obj.vel.length());
+                obj.acc += gravity + drag * obj.inv_mass();
+                obj.vel += obj.acc * dt;
+                obj.vel = obj.vel.clamp_length(max_speed);
+                obj.debug_acc = obj.acc;
+                obj.acc = Vec2::new(0.0, 0.0);
+                let old_pos = obj.pos;
+                obj.pos += obj.vel * dt;
+                obj.angle += obj.angular_vel * dt;
+
+                // Flip only on the step the ball enters a zone, not every step it's inside
+                if flip_zones.iter().any(|z| z.contains_point(obj.pos) && !z.contains_point(old_pos)) {
+                    obj.gravity_flipped = !obj.gravity_flipped;
+                }
+            } else if obj.fixed && let Some(path) = &obj.kinematic {
+                // Velocity is kept so collisions know how fast the mover is going
+                let next = path.position_at(obj.initial_pos, path_time);
+                obj.vel = (next - obj.pos) / dt;
+                obj.pos = next;
+            }
+        });
+
+        // A NaN or infinity would carry the ball off for good and spread to whatever it
+        // touches, put it back at its start before the solver sees it
+        let mut reset = 0;
+        for obj in &mut self.objects {
+            if !obj.pos.is_finite() || !obj.vel.is_finite() {
+                obj.pos = obj.initial_pos;
+                obj.vel = obj.initial_vel;
+                obj.acc = Vec2::new(0.0, 0.0);
+                obj.angular_vel = 0.0;
+                reset += 1;
+            }
+        }
+        if reset > 0 {
+            self.nonfinite_resets += reset;
+            self.status_message = Some(format!("{} ball reset(s) after a blow-up this attempt", self.nonfinite_resets));
+        }
+
+        // A mover pulling away from a sleeping ball would leave it hanging in the air
+        let movers: Vec<Circle> = self.objects.iter()
+            .filter(|o| o.fixed && o.kinematic.is_some())
+            .map(|o| o.circle())
+            .collect();
+        if !movers.is_empty() {
+            for obj in self.objects.iter_mut().filter(|o| o.sleeping) {
+                if movers.iter().any(|m| m.penetration(&obj.circle()) > -CONTACT_MARGIN) {
+                    obj.wake();
+                }
+            }
+        }
+
+        // Arm the win check once the player ball has left its spawn point
+        if !self.win_armed {
+            const WIN_ARM_DISTANCE: f32 = 5.0;
+            self.win_armed = self.objects.iter()
+                .any(|o| o.is_player && o.pos.distance(&o.initial_pos) > WIN_ARM_DISTANCE);
+        }
+
+        // Boundary collisions
+        let mut fell_into_pit = false;
+        let floor = self.restitution_floor;
+        for obj in &mut self.objects {
+            if obj.fixed || obj.lost || obj.sleeping { continue; }
+
+            if self.boundary_mode == BoundaryMode::WrapAround {
+                if obj.pos.x < BOARDER_START {
+                    obj.pos.x += self.bounds.0;
+                } else if obj.pos.x > self.bounds.0 + BOARDER_START {
+                    obj.pos.x -= self.bounds.0;
+                }
+                if obj.pos.y < 0.0 {
+                    obj.pos.y += self.bounds.1;
+                } else if obj.pos.y > self.bounds.1 {
+                    obj.pos.y -= self.bounds.1;
+                }
+                continue;
+            }
+            
+            if obj.pos.x - obj.radius < BOARDER_START {
+                obj.pos.x = obj.radius + BOARDER_START;
+                obj.roll_against(Vec2::new(1.0, 0.0), obj.mass * obj.vel.x.abs());
+                obj.vel.x = -obj.vel.x * floored_restitution(obj.vel.x, obj.bounciness, floor);
+            } else if obj.pos.x + obj.radius > self.bounds.0 + BOARDER_START {
+                obj.pos.x = self.bounds.0 - obj.radius + BOARDER_START;
+                obj.roll_against(Vec2::new(-1.0, 0.0), obj.mass * obj.vel.x.abs());
+                obj.vel.x = -obj.vel.x * floored_restitution(obj.vel.x, obj.bounciness, floor);
+            }
+
+            if obj.pos.y - obj.radius < 15.0 && self.boundary_mode != BoundaryMode::OpenTop {
+                obj.pos.y = obj.radius + 15.0;
+                obj.roll_against(Vec2::new(0.0, 1.0), obj.mass * obj.vel.y.abs());
+                obj.vel.y = -obj.vel.y * floored_restitution(obj.vel.y, obj.bounciness, floor);
+            } else if self.boundary_mode == BoundaryMode::BottomlessPit {
+                if obj.pos.y - obj.radius > self.bounds.1 {
+                    obj.lost = true;
+                    fell_into_pit = true;
+                }
+            } else if obj.pos.y + obj.radius > self.bounds.1 {
+                obj.pos.y = self.bounds.1 - obj.radius;
+                obj.roll_against(Vec2::new(0.0, -1.0), obj.mass * obj.vel.y.abs());
+                obj.vel.y = -obj.vel.y * floored_restitution(obj.vel.y, obj.bounciness, floor);
+            }
+        }
+
+        if fell_into_pit && !was_won && self.lost_needed_ball() {
+            self.reset_simulation();
+            self.status_message = Some("A needed ball fell into the pit, try again".to_string());
+            return;
+        }
+
+        let mut collisions: Vec<CollisionEvent> = Vec::new();
+
+        // Object-to-object collisions: find candidate pairs (in parallel when enabled),
+        // group them into islands of touching bodies and solve each island on its own.
+        // Single contacts resolve once; stacks get extra passes so they firm up.
+        let mut pairs = self.contact_pairs();
+        // Deepest contacts first, a shallow one resolved early just gets pushed back in
+        pairs.sort_by(|&a, &b| self.penetration(b).total_cmp(&self.penetration(a)));
+        self.apply_pair_budget(&mut pairs);
+        let islands = self.build_islands(&pairs);
+
+        // Broadphase: wall boxes grown by the biggest ball so no contact is culled
+        let max_radius = self.objects.iter().map(|o| o.radius).fold(0.0, f32::max);
+        let wall_boxes: Vec<Aabb> = self.walls.iter()
+            .map(|w| Aabb::from_points(w.start, w.end).expanded(max_radius + w.thickness / 2.0))
+            .collect();
+
+        // Each iteration re-reads the positions and velocities left by the previous one
+        for _ in 0..self.solver_iterations.max(1) {
+            for island in &islands {
+                let passes = if island.len() > 1 { self.stack_passes } else { 1 };
+                for _ in 0..passes {
+                    for &(i, j) in island {
+                        self.resolve_object_pair(i, j, &mut collisions);
+                    }
+                }
+            }
+            self.resolve_wall_contacts(&wall_boxes, &mut collisions);
+        }
+
+        self.update_switches();
+
+        // Breakables that took too much punishment shatter and blast their neighbours away
+        let shattered: Vec<(Vec2, f32)> = self.objects.iter_mut()
+            .filter(|o| !o.lost && o.health.is_some_and(|health| o.damage >= health))
+            .map(|o| {
+                o.lost = true;
+                (o.pos, o.radius)
+            })
+            .collect();
+        for (center, radius) in shattered {
+            self.blast(center, radius);
+            self.shake = SHAKE_TIME;
+        }
+
+        self.check_goals_complete();
+
+        // Put objects to sleep once they've been nearly still for a while. A slow ball that
+        // keeps creeping along stays awake, and sleepers keep their velocity for when they wake
+        if self.allow_sleeping {
+            for obj in &mut self.objects {
+                if obj.fixed || obj.lost || obj.sleeping { continue; }
+                if obj.vel.length() < SLEEP_SPEED && obj.pos.distance(&obj.sleep_anchor) < SLEEP_DRIFT {
+                    obj.sleep_timer += dt;
+                    if obj.sleep_timer >= SLEEP_TIME {
+                        obj.sleeping = true;
+                    }
+                } else {
+                    obj.sleep_timer = 0.0;
+                    obj.sleep_anchor = obj.pos;
+                }
+            }
+        }
+
+        self.sim_time += dt;
+        self.step_count += 1;
+
+        // Everything has settled short of a win, so no further goal can be covered
+        if !was_won && matches!(self.game_state, GameState::Simulating) && self.all_at_rest() {
+            self.rest_timer += dt;
+        } else {
+            self.rest_timer = 0.0;
+        }
+        if self.rest_timer >= SLEEP_TIME && self.sim_time > REST_FAIL_MIN_TIME {
+            self.reset_simulation();
+            self.status_message = Some("No more motion, try again".to_string());
+            return;
+        }
+
+        for event in collisions.iter().filter(|e| e.impulse > MIN_HIT_IMPULSE) {
+            if self.break_on_collision {
+                self.paused = true;
+            }
+            if self.show_collision_values {
+                self.spawn_label(event.pos, format!("{:.0}", event.impulse));
+            }
+            // Only hits the player's shot led to count toward a combo
+            let from_player = std::iter::once(event.a).chain(event.b)
+                .any(|i| self.objects[i].is_player || self.objects[i].last_touched_by.is_some());
+            if self.combo_mode && from_player && matches!(self.game_state, GameState::Simulating) {
+                self.register_combo_hit(event.pos);
+            }
+            if event.impulse > SHAKE_IMPULSE {
+                self.shake = SHAKE_TIME;
+            }
+            if let Some(record) = &event.record {
+                if self.lesson_log.len() == LESSON_LOG_LEN {
+                    self.lesson_log.remove(0);
+                }
+                self.lesson_log.push(record.clone());
+                self.lesson_selected = Some(self.lesson_log.len() - 1);
+                self.paused = true;
+            }
+            if let Some(observer) = &self.collision_observer {
+                match event.b {
+                    Some(b) => observer.borrow_mut().on_collision(event.a, b, event.impulse),
+                    None => observer.borrow_mut().on_wall(event.a, event.impulse),
+                }
+            }
+        }
+
+        if !was_won && matches!(self.game_state, GameState::Won) {
+            self.win_steps = self.step_count;
+            self.win_sim_time = self.sim_time;
+            self.reset_combo();
+            self.award_medal();
+            let best = self.best_steps.entry(self.level).or_insert(self.step_count);
+            *best = (*best).min(self.step_count);
+            let walls_used = self.count_user_walls();
+            let stats = self.level_stats.entry(self.level).or_default();
+            stats.walls_used = walls_used;
+            stats.win_time = self.sim_time;
+        }
+
+        self.update_goal_tension(dt);
+
+        if self.record_trajectory && !was_won
+            && let Some(player) = self.objects.iter().find(|o| o.is_player) {
+            self.trajectory.push(TrajectorySample {
+                time: self.sim_time,
+                pos: player.pos,
+                vel: player.vel,
+            });
+        }
+    }
+
+    fn contact_pairs(&self) -> Vec<(usize, usize)> {
+        let objects = &self.objects;
+        let no_collide = &self.no_collide_pairs;
+        let len = objects.len();
+        let candidates = move |i: usize| {
+            (i + 1..len)
+                .filter(move |&j| is_contact_candidate(&objects[i], &objects[j]))
+                .filter(move |&j| !no_collide.iter().any(|&p| p == (i, j) || p == (j, i)))
+                .map(move |j| (i, j))
+        };
+
+        #[cfg(feature = "parallel")]
+        if self.parallel_step {
+            use rayon::prelude::*;
+            return (0..len).into_par_iter().flat_map_iter(candidates).collect();
+        }
+        (0..len).flat_map(candidates).collect()
+    }
+
+    // Over budget, pairs that have waited longest go first and then the deepest; the rest
+    // wait for the next step with their wait counted, so none is put off for good
+    fn apply_pair_budget(&mut self, pairs: &mut Vec<(usize, usize)>) {
+        if pairs.len() <= self.pair_budget {
+            self.deferred_pairs.clear();
+            return;
+        }
+        let waited = |pair: &(usize, usize)| self.deferred_pairs.get(pair).copied().unwrap_or(0);
+        // Stable, so equal waits keep their deepest-first order
+        pairs.sort_by_key(|pair| std::cmp::Reverse(waited(pair)));
+        let deferred = pairs.split_off(self.pair_budget);
+        self.deferred_pairs = deferred.iter().map(|pair| (*pair, waited(pair) + 1)).collect();
+        pairs.sort_by(|&a, &b| self.penetration(b).total_cmp(&self.penetration(a)));
+    }
+
+    // Push balls out of walls and bounce them off
+    fn resolve_wall_contacts(&mut self, wall_boxes: &[Aabb], collisions: &mut Vec<CollisionEvent>) {
+        for (index, obj) in self.objects.iter_mut().enumerate() {
+            if obj.fixed || obj.lost || obj.sleeping { continue; }
+            let ball_box = Aabb::around_circle(obj.pos, obj.radius);
+            
+            for (wall, wall_box) in self.walls.iter().zip(wall_boxes) {
+                if !wall.active || !wall_box.intersects(&ball_box) { continue; }
+
+                if let Some((normal, dist)) = wall.contact(obj.pos, obj.radius) {
+                    let vel_normal = obj.vel.dot(&normal);
+                    // A ball already moving away from the wall (e.g. one that crossed it
+                    // at speed) is left alone instead of being yanked back through
+                    let leaving = vel_normal * dist > 0.0;
+                    let reach = obj.radius + wall.thickness / 2.0;
+                    if !(self.leading_hemisphere_walls && leaving) {
+                        let penetration = reach - dist.abs();
+                        obj.pos += normal * (penetration * dist.signum());
+                        
+                        if vel_normal * dist < 0.0 {
+                            let restitution = floored_restitution(
+                                vel_normal,
+                                wall.restitution.unwrap_or(obj.bounciness),
+                                self.restitution_floor,
+                            );
+                            let impulse = obj.mass * vel_normal.abs() * (1.0 + restitution);
+                            obj.roll_against(normal * dist.signum(), impulse);
+                            // A perfect mirror bounce, minus the normal speed restitution doesn't keep
+                            obj.vel = obj.vel.reflect(&normal) + normal * (vel_normal * (1.0 - restitution));
+                            // Muddy walls also eat slide speed, independent of bounce height
+                            obj.vel *= 1.0 - self.contact_damping;
+                            if impulse > PLASTIC_IMPULSE_THRESHOLD {
+                                obj.dent();
+                            }
+                            // Resting contact doesn't wear breakables down, only real hits
+                            if impulse > MIN_HIT_IMPULSE {
+                                obj.damage += impulse;
+                            }
+                            collisions.push(CollisionEvent {
+                                pos: obj.pos - normal * (obj.radius * dist.signum()),
+                                impulse,
+                                a: index,
+                                b: None,
+                                record: None,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Win once every goal in the level has been hit at least once
+    fn check_goals_complete(&mut self) {
+        if self.satisfied_goals.is_empty() || matches!(self.game_state, GameState::Won) {
+            return;
+        }
+        let all_hit = self.objects.iter().enumerate()
+            .filter(|(_, o)| o.is_goal)
+            .all(|(i, _)| self.satisfied_goals.contains(&i));
+        if all_hit {
+            self.game_state = GameState::Won;
+            self.win_time = Some(self.clock);
+            if self.no_wall_challenge && !self.walls.iter().any(|w| w.is_user_placed) {
+                self.no_wall_wins += 1;
+            }
+        }
+    }
+
+    // Build tension while a ball that could win is closing in on the goal
+    fn update_goal_tension(&mut self, dt: f32) {
+        let goals = self.objects.iter().filter(|o| o.is_goal && !o.lost);
+        let gap = goals.flat_map(|goal| {
+            self.objects.iter()
+                .filter(|o| !o.is_goal && !o.is_player && !o.fixed && !o.lost)
+                .map(move |o| o.pos.distance(&goal.pos) - o.radius - goal.radius)
+        }).reduce(f32::min);
+
+        let closing = matches!((gap, self.last_goal_gap), (Some(gap), Some(last)) if gap < last);
+        self.goal_tension = match gap {
+            Some(gap) if closing && gap < GOAL_ALERT_DISTANCE => 1.0 - gap.max(0.0) / GOAL_ALERT_DISTANCE,
+            _ => (self.goal_tension - dt * 2.0).max(0.0),
+        };
+        self.last_goal_gap = gap;
+    }
+
+    // How far a pair of balls overlaps, negative when they're apart
+    fn penetration(&self, (i, j): (usize, usize)) -> f32 {
+        self.objects[i].circle().penetration(&self.objects[j].circle())
+    }
+
+    // Group contact pairs into islands: sets of dynamic bodies linked by contacts or
+    // springs. Fixed bodies never join islands, they can't carry impulses between them.
+    fn build_islands(&self, pairs: &[(usize, usize)]) -> Vec<Vec<(usize, usize)>> {
+        fn find(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+
+        let mut parent: Vec<usize> = (0..self.objects.len()).collect();
+        let links = pairs.iter().copied().chain(
+            self.springs.iter().filter_map(|s| Some((s.object_index, s.anchor?))),
+        );
+        for (a, b) in links {
+            let (Some(obj_a), Some(obj_b)) = (self.objects.get(a), self.objects.get(b)) else { continue };
+            if obj_a.fixed || obj_b.fixed { continue; }
+            let (root_a, root_b) = (find(&mut parent, a), find(&mut parent, b));
+            parent[root_a] = root_b;
+        }
+
+        // Islands keep the order their first pair appeared in, so solving is deterministic
+        let mut island_of_root: Vec<Option<usize>> = vec![None; self.objects.len()];
+        let mut islands: Vec<Vec<(usize, usize)>> = Vec::new();
+        for &(i, j) in pairs {
+            let member = if self.objects[i].fixed { j } else { i };
+            let root = find(&mut parent, member);
+            let island = *island_of_root[root].get_or_insert_with(|| {
+                islands.push(Vec::new());
+                islands.len() - 1
+            });
+            islands[island].push((i, j));
+        }
+        islands
+    }
+
+    fn resolve_object_pair(&mut self, i: usize, j: usize, collisions: &mut Vec<CollisionEvent>) {
+        let (obj1, obj2) = {
+            let (left, right) = self.objects.split_at_mut(j);
+            (&mut left[i], &mut right[0])
+        };
+
+        // Earlier pairs in this step may already have pushed these two apart
+        let delta_pos = obj2.pos - obj1.pos;
+        let dist_sq = delta_pos.length_squared();
+        let min_dist = obj1.radius + obj2.radius;
+        // Out of reach for both the goal check and a collision, skip the sqrt
+        let reach = min_dist + self.goal_tolerance;
+        if dist_sq >= reach * reach {
+            return;
+        }
+        let penetration = min_dist - dist_sq.sqrt();
+
+        // Check for goal hit, the win itself waits until every goal is covered.
+        // A graze within the level's tolerance counts even without touching
+        if self.win_armed && !matches!(self.game_state, GameState::Won) && penetration > -self.goal_tolerance {
+            if is_goal_hit(obj1, obj2) && self.satisfied_goals.insert(i) {
+                self.winning_hit = Some((j, obj2.last_touched_by));
+            }
+            if is_goal_hit(obj2, obj1) && self.satisfied_goals.insert(j) {
+                self.winning_hit = Some((i, obj1.last_touched_by));
+            }
+        }
+
+        if dist_sq >= min_dist * min_dist {
+            return;
+        }
+
+        // Being hit wakes a sleeping object
+        if obj1.sleeping {
+            obj1.wake();
+        }
+        if obj2.sleeping {
+            obj2.wake();
+        }
+
+        let normal = delta_pos.normalized();
+        // Heavier balls give way less, immovable ones (fixed, kinematic) not at all.
+        // Zero or non-finite masses (e.g. a massless ball) can't share a push or an impulse
+        let (inv_mass1, inv_mass2) = (obj1.inv_mass(), obj2.inv_mass());
+        let inv_mass_sum = inv_mass1 + inv_mass2;
+        if inv_mass_sum <= 0.0 || !inv_mass_sum.is_finite() {
+            return;
+        }
+
+        // Softer settings leave part of the overlap for later steps, for a springier feel
+        let separation = normal * (penetration * self.separation_stiffness / inv_mass_sum);
+        obj1.pos -= separation * inv_mass1;
+        obj2.pos += separation * inv_mass2;
+
+        let rel_vel = obj2.vel - obj1.vel;
+        let vel_along_normal = rel_vel.dot(&normal);
+        // Already separating: pushing again would pull them back together
+        if vel_along_normal >= 0.0 {
+            return;
+        }
+
+        let least_bounciness = floored_restitution(
+            vel_along_normal,
+            obj1.bounciness.min(obj2.bounciness),
+            self.restitution_floor,
+        );
+        let mut impulse_mag = -(1.0 + least_bounciness) * vel_along_normal;
+        impulse_mag /= inv_mass_sum;
+
+        // Whichever ball was closing in harder is the hitter and passes on whose shot set it moving
+        let source = |obj: &PhysicsObject, index: usize| if obj.is_player { Some(index) } else { obj.last_touched_by };
+        if obj1.vel.dot(&normal) >= -obj2.vel.dot(&normal) {
+            obj2.last_touched_by = source(obj1, i);
+        } else {
+            obj1.last_touched_by = source(obj2, j);
+        }
+
+        let before = (obj1.vel, obj2.vel);
+        // A repulsive object shoves the other ball away and never takes the recoil itself
+        if !obj1.fixed && !obj1.repulsive {
+            obj1.vel -= (normal * impulse_mag) * inv_mass1;
+        }
+        if !obj2.fixed && !obj2.repulsive {
+            obj2.vel += (normal * impulse_mag) * inv_mass2;
+        }
+
+        // Friction against a mover drags the ball toward its surface speed, at most
+        // PLATFORM_FRICTION times the normal impulse (Coulomb)
+        if obj1.kinematic.is_some() || obj2.kinematic.is_some() {
+            let tangent = normal.perp();
+            let slip = (obj2.vel - obj1.vel).dot(&tangent);
+            let limit = PLATFORM_FRICTION * impulse_mag * inv_mass_sum;
+            let change = slip.clamp(-limit, limit);
+            obj1.vel += tangent * (change * inv_mass1 / inv_mass_sum);
+            obj2.vel -= tangent * (change * inv_mass2 / inv_mass_sum);
+        }
+
+        if impulse_mag > PLASTIC_IMPULSE_THRESHOLD {
+            obj1.dent();
+            obj2.dent();
+        }
+        if impulse_mag > MIN_HIT_IMPULSE {
+            obj1.damage += impulse_mag;
+            obj2.damage += impulse_mag;
+        }
+
+        collisions.push(CollisionEvent {
+            pos: obj1.pos + normal * obj1.radius,
+            impulse: impulse_mag,
+            a: i,
+            b: Some(j),
+            record: self.lesson_mode.then_some(CollisionRecord {
+                time: self.sim_time,
+                a: i,
+                b: j,
+                masses: (obj1.mass, obj2.mass),
+                fixed: (obj1.fixed, obj2.fixed),
+                before,
+                after: (obj1.vel, obj2.vel),
+                normal,
+                restitution: least_bounciness,
+                impulse: impulse_mag,
+            }),
+        });
+    }
+
+    // Rasterize the level's starting layout into a small offscreen image
+    fn render_thumbnail(&self, ctx: &egui::Context, size: [usize; 2]) -> egui::TextureHandle {
+        let mut image = egui::ColorImage::filled(size, egui::Color32::from_gray(20));
+        let area = self.play_area();
+        let scale = size[0] as f32 / (area.max.x - area.min.x);
+        let to_pixel = |p: Vec2| ((p.x - area.min.x) * scale, (p.y - area.min.y) * scale);
+        let mut plot = |x: f32, y: f32, color: egui::Color32| {
+            if x >= 0.0 && y >= 0.0 && (x as usize) < size[0] && (y as usize) < size[1] {
+                image.pixels[y as usize * size[0] + x as usize] = color;
+            }
+        };
+
+        for wall in &self.walls {
+            let (x0, y0) = to_pixel(wall.start);
+            let (x1, y1) = to_pixel(wall.end);
+            let samples = ((x1 - x0).abs().max((y1 - y0).abs()) * 2.0).ceil().max(1.0) as usize;
+            for k in 0..=samples {
+                let t = k as f32 / samples as f32;
+                plot(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, egui::Color32::WHITE);
+            }
+        }
+
+        for obj in &self.objects {
+            let (cx, cy) = to_pixel(obj.initial_pos);
+            let r = (obj.radius * scale).max(1.0);
+            let color = if obj.is_goal { egui::Color32::GOLD } else { obj.color };
+            for dy in -(r.ceil() as i32)..=r.ceil() as i32 {
+                for dx in -(r.ceil() as i32)..=r.ceil() as i32 {
+                    if ((dx * dx + dy * dy) as f32) <= r * r {
+                        plot(cx + dx as f32, cy + dy as f32, color);
+                    }
+                }
+            }
+        }
+
+        ctx.load_texture(format!("level-{}-thumbnail", self.level), image, egui::TextureOptions::LINEAR)
+    }
+
+    // Pointer position in world coordinates, undoing any scale-to-fit transform
+    fn world_pointer(&self, ui: &egui::Ui) -> Option<egui::Pos2> {
+        ui.input(|i| i.pointer.hover_pos()).map(|pos| self.world_transform.inverse() * pos)
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        let painter = ui.painter();
         
-        // Draw objects
+        // draw boundaries
+        painter.rect_stroke(
+            self.canvas_rect,
+            0.0,
+            egui::Stroke::new(4.0, egui::Color32::LIGHT_GRAY),
+            egui::StrokeKind::Inside
+        );
+
+        // Draw gravity zones as a tint with an arrow scaled by their gravity
+        for zone in &self.gravity_zones {
+            let rect = egui::Rect::from_min_max(
+                egui::pos2(zone.rect.min.x, zone.rect.min.y),
+                egui::pos2(zone.rect.max.x, zone.rect.max.y),
+            );
+            let heavier = zone.gravity.length() > self.gravity.length();
+            let tint = if heavier {
+                egui::Color32::from_rgba_unmultiplied(255, 120, 60, 25)
+            } else {
+                egui::Color32::from_rgba_unmultiplied(120, 200, 255, 25)
+            };
+            painter.rect_filled(rect, 0.0, tint);
+            painter.arrow(
+                rect.center(),
+                egui::vec2(zone.gravity.x, zone.gravity.y) * 0.1,
+                egui::Stroke::new(2.0, tint.gamma_multiply(6.0)),
+            );
+        }
+
+        // Flip zones get a purple tint with opposing arrows
+        for zone in &self.flip_zones {
+            let rect = egui::Rect::from_min_max(
+                egui::pos2(zone.min.x, zone.min.y),
+                egui::pos2(zone.max.x, zone.max.y),
+            );
+            let tint = egui::Color32::from_rgba_unmultiplied(180, 90, 255, 30);
+            let stroke = egui::Stroke::new(2.0, tint.gamma_multiply(6.0));
+            painter.rect_filled(rect, 0.0, tint);
+            painter.arrow(rect.center() - egui::vec2(15.0, -15.0), egui::vec2(0.0, -30.0), stroke);
+            painter.arrow(rect.center() + egui::vec2(15.0, -15.0), egui::vec2(0.0, 30.0), stroke);
+        }
+
+        // Draw soft bodies as a filled fan around their center
+        for body in &self.soft_bodies {
+            let mut mesh = egui::Mesh::default();
+            let center = self.objects[body.center].pos;
+            mesh.colored_vertex(egui::pos2(center.x, center.y), body.color.gamma_multiply(0.8));
+            for &idx in &body.ring {
+                let p = self.objects[idx].pos;
+                mesh.colored_vertex(egui::pos2(p.x, p.y), body.color);
+            }
+            let n = body.ring.len() as u32;
+            for i in 0..n {
+                mesh.add_triangle(0, 1 + i, 1 + (i + 1) % n);
+            }
+            painter.add(egui::Shape::mesh(mesh));
+        }
+        let soft_body_members: std::collections::HashSet<usize> = self.soft_bodies.iter()
+            .flat_map(|b| b.ring.iter().copied().chain(std::iter::once(b.center)))
+            .collect();
+
+        // Draw springs
+        for spring in &self.springs {
+            // Soft body springs are implied by the filled shape
+            if soft_body_members.contains(&spring.object_index) { continue; }
+            if let Some(obj) = self.objects.get(spring.object_index) {
+                let anchor_pos = if let Some(anchor_idx) = spring.anchor {
+                    if let Some(anchor_obj) = self.objects.get(anchor_idx) {
+                        egui::pos2(anchor_obj.pos.x, anchor_obj.pos.y)
+                    } else {
+                        continue;
+                    }
+                } else {
+                    egui::pos2(spring.anchor_pos.x, spring.anchor_pos.y)
+                };
+
+                let anchor = Vec2::new(anchor_pos.x, anchor_pos.y);
+                let dist = obj.pos.distance(&anchor);
+                // Red when stretched, blue when squeezed, stronger the further from rest
+                let strain = dist - spring.rest_length;
+                let strained = if strain > 0.0 { egui::Color32::RED } else { egui::Color32::from_rgb(60, 120, 255) };
+                let color = egui::Color32::DARK_GRAY.lerp_to_gamma(strained, (strain.abs() / SPRING_STRAIN_COLOR_RANGE).min(1.0));
+
+                let points: Vec<egui::Pos2> = spring_zigzag(anchor, obj.pos).iter()
+                    .map(|p| egui::pos2(p.x, p.y))
+                    .collect();
+                for i in 0..points.len()-1 {
+                    painter.line_segment(
+                        [points[i], points[i+1]],
+                        egui::Stroke::new(2.0, color),
+                    );
+                }
+            }
+        }
+
+        // Draggable handles on fixed anchors
+        if self.editor_mode && matches!(self.game_state, GameState::Planning) {
+            for spring in self.springs.iter().filter(|s| s.anchor.is_none()) {
+                let rect = egui::Rect::from_center_size(
+                    egui::pos2(spring.anchor_pos.x, spring.anchor_pos.y),
+                    egui::vec2(ANCHOR_HANDLE_SIZE * 2.0, ANCHOR_HANDLE_SIZE * 2.0),
+                );
+                painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.5, egui::Color32::YELLOW), egui::StrokeKind::Middle);
+            }
+        }
+
+        // Draw walls
+        for wall in &self.walls {
+            let color = if wall.is_user_placed {
+                egui::Color32::from_rgb(100, 200, 255)
+            } else {
+                egui::Color32::WHITE
+            };
+
+            // Bouncy walls glow
+            if let Some(restitution) = wall.restitution
+                && restitution >= 1.0 {
+                draw_capsule(painter, wall.start, wall.end, 16.0, egui::Color32::from_rgb(255, 230, 80).gamma_multiply(0.35));
+            }
+            
+            let color = if wall.active { color } else { color.gamma_multiply(0.2) };
+            draw_capsule(painter, wall.start, wall.end, wall.thickness, color);
+        }
+
+        // Switches, wired to their doors while editing
+        for switch in &self.switches {
+            let center = egui::pos2(switch.pos.x, switch.pos.y);
+            let color = if switch.flipped { egui::Color32::from_rgb(80, 220, 120) } else { egui::Color32::from_rgb(120, 90, 40) };
+            painter.circle_filled(center, switch.radius, color);
+            painter.circle_stroke(center, switch.radius, egui::Stroke::new(2.0, egui::Color32::WHITE));
+            if self.editor_mode {
+                for wall in switch.target_wall_indices.iter().filter_map(|&i| self.walls.get(i)) {
+                    let middle = (wall.start + wall.end) * 0.5;
+                    painter.extend(egui::Shape::dashed_line(
+                        &[center, egui::pos2(middle.x, middle.y)],
+                        egui::Stroke::new(1.0, color),
+                        5.0,
+                        5.0,
+                    ));
+                }
+            }
+        }
+
+        // Draw wall preview
+        if let Some(start) = self.placing_wall
+            && let Some(pointer_pos) = self.world_pointer(ui) {
+            draw_capsule(
+                painter,
+                start,
+                Vec2::new(pointer_pos.x, pointer_pos.y),
+                WALL_THICKNESS,
+                egui::Color32::from_rgba_premultiplied(100, 200, 255, 150),
+            );
+        }
+        
+        // Hint walls as dashed outlines to copy
+        if matches!(self.game_state, GameState::Planning)
+            && let Some(index) = self.hint_shown
+            && let Some(walls) = self.hint_solutions.get(&self.level).and_then(|s| s.get(index)) {
+            for wall in walls {
+                painter.extend(egui::Shape::dashed_line(
+                    &[egui::pos2(wall.start.x, wall.start.y), egui::pos2(wall.end.x, wall.end.y)],
+                    egui::Stroke::new(WALL_THICKNESS, egui::Color32::from_rgba_unmultiplied(120, 255, 120, 120)),
+                    10.0,
+                    6.0,
+                ));
+            }
+        }
+
+        // Draw ruler with length and angles
+        if let Some(start) = self.measure_start
+            && let Some(pointer_pos) = self.world_pointer(ui) {
+            let ruler = Vec2::new(pointer_pos.x, pointer_pos.y) - start;
+            // Screen y points down, flip it so counter-clockwise angles are positive
+            let angle = (-ruler.y).atan2(ruler.x).to_degrees();
+            let mut text = format!("{:.0} px\n{:.1}° from horizontal", ruler.length(), angle);
+            if let Some(player) = self.objects.iter().find(|o| o.is_player) {
+                let vel_angle = (-player.vel.y).atan2(player.vel.x).to_degrees();
+                let relative = (angle - vel_angle + 540.0).rem_euclid(360.0) - 180.0;
+                text.push_str(&format!("\n{relative:.1}° from launch"));
+            }
+
+            painter.line_segment(
+                [egui::pos2(start.x, start.y), pointer_pos],
+                egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 220, 0)),
+            );
+            painter.text(
+                pointer_pos + egui::vec2(12.0, 12.0),
+                egui::Align2::LEFT_TOP,
+                text,
+                egui::FontId::monospace(13.0),
+                egui::Color32::from_rgb(255, 220, 0),
+            );
+        }
+
+        // Kinematic movers show the loop they follow
         for obj in &self.objects {
+            if let Some(path) = &obj.kinematic {
+                let mut points: Vec<egui::Pos2> = std::iter::once(obj.initial_pos)
+                    .chain(path.waypoints.iter().copied())
+                    .chain(std::iter::once(obj.initial_pos))
+                    .map(|p| egui::pos2(p.x, p.y))
+                    .collect();
+                points.dedup();
+                painter.extend(egui::Shape::dashed_line(
+                    &points,
+                    egui::Stroke::new(1.5, egui::Color32::from_gray(120)),
+                    8.0,
+                    6.0,
+                ));
+            }
+        }
+
+        // Draw objects
+        for (i, obj) in self.objects.iter().enumerate().filter(|(_, o)| !o.lost) {
             let mut color = obj.color;
             if obj.is_goal && matches!(self.game_state, GameState::Won) {
                 color = egui::Color32::from_rgb(255, 255, 100);
+            } else if obj.is_goal && self.satisfied_goals.contains(&i) {
+                color = egui::Color32::GOLD;
             }
             
-            painter.circle_filled(
-                egui::pos2(obj.pos.x, obj.pos.y),
-                obj.radius,
-                color,
-            );
+            if let Some(texture) = &obj.texture {
+                let rect = egui::Rect::from_center_size(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    egui::Vec2::splat(obj.radius * 2.0),
+                );
+                egui::Image::new(texture)
+                    .rotate(obj.angle, egui::Vec2::splat(0.5))
+                    .paint_at(ui, rect);
+            } else {
+                painter.circle_filled(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius,
+                    color,
+                );
+            }
             
+            // Spoke so spin and rolling are visible, sprites already show it
+            if !obj.fixed && obj.texture.is_none() {
+                let spoke = egui::vec2(obj.angle.cos(), obj.angle.sin()) * obj.radius * 0.8;
+                painter.line_segment(
+                    [egui::pos2(obj.pos.x, obj.pos.y), egui::pos2(obj.pos.x, obj.pos.y) + spoke],
+                    egui::Stroke::new(2.0, egui::Color32::BLACK.gamma_multiply(0.4)),
+                );
+            }
+
+            // Pinned balls get the blocker outline so they read as immovable
+            if obj.fixed {
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius,
+                    egui::Stroke::new(3.0, egui::Color32::from_rgb(60, 60, 60)),
+                );
+            }
+
+            // Breakables get a ring that reddens as they take damage
+            if let Some(health) = obj.health {
+                let worn = (obj.damage / health).min(1.0);
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius - 2.0,
+                    egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 200, 120).lerp_to_gamma(egui::Color32::RED, worn)),
+                );
+            }
+
+            // Warning ring and halo for repulsive balls
+            if obj.repulsive {
+                let warning = egui::Color32::from_rgb(255, 60, 200);
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius,
+                    egui::Stroke::new(3.0, warning),
+                );
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius + REPULSION_RANGE,
+                    egui::Stroke::new(1.0, warning.gamma_multiply(0.3)),
+                );
+            }
+
+            // Faint ring marking the attractors of orbital mode
+            if obj.planet {
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius + 6.0,
+                    egui::Stroke::new(1.0, egui::Color32::from_rgb(120, 170, 255).gamma_multiply(0.6)),
+                );
+            }
+
             // Draw outline for player ball
             if obj.is_player {
                 painter.circle_stroke(
@@ -839,61 +4280,178 @@ fn setup_level_1(&mut self) {
                 );
             }
             
+            // Pulse the goal's outline while a ball closes in on it
+            if obj.is_goal && self.goal_tension > 0.0 {
+                let pulse = 0.5 + 0.5 * (self.sim_time * 20.0).sin();
+                painter.circle_stroke(
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    obj.radius + 3.0,
+                    egui::Stroke::new(
+                        1.0 + 4.0 * self.goal_tension * pulse,
+                        egui::Color32::from_rgb(255, 140, 0).gamma_multiply(self.goal_tension),
+                    ),
+                );
+            }
+
             // Draw star for goal
             if obj.is_goal {
-                let star_size = 15.0;
-                for i in 0..5 {
-                    let angle1 = std::f32::consts::PI * 2.0 * i as f32 / 5.0 - std::f32::consts::PI / 2.0;
-                    let angle2 = std::f32::consts::PI * 2.0 * (i as f32 + 0.5) / 5.0 - std::f32::consts::PI / 2.0;
-                    
-                    let p1 = egui::pos2(
-                        obj.pos.x + angle1.cos() * star_size,
-                        obj.pos.y + angle1.sin() * star_size
-                    );
-                    let p2 = egui::pos2(
-                        obj.pos.x + angle2.cos() * star_size * 0.5,
-                        obj.pos.y + angle2.sin() * star_size * 0.5
+                for (p1, p2) in goal_star(obj.pos) {
+                    painter.line_segment(
+                        [egui::pos2(p1.x, p1.y), egui::pos2(p2.x, p2.y)],
+                        egui::Stroke::new(2.0, egui::Color32::WHITE),
                     );
-                    
-                    painter.line_segment([p1, p2], egui::Stroke::new(2.0, egui::Color32::WHITE));
                 }
             }
         }
 
-        // Draw velocity arrow for player ball in planning mode
-        if matches!(self.game_state, GameState::Planning) {
-            if let Some(player) = self.objects.iter().find(|o| o.is_player) {
-                let arrow_scale = 0.15;
-                let end_pos = egui::pos2(
-                    player.pos.x + player.vel.x * arrow_scale,
-                    player.pos.y + player.vel.y * arrow_scale
-                );
-                
+        // Acceleration arrows: gravity, springs, zones and drag summed, from the last step
+        if self.debug_draw && matches!(self.game_state, GameState::Simulating) {
+            const ACC_ARROW_SCALE: f32 = 0.1;
+            const MAX_ACC_ARROW_LENGTH: f32 = 100.0;
+            for obj in self.objects.iter().filter(|o| !o.fixed && !o.lost && !o.sleeping) {
+                let length = (obj.debug_acc.length() * ACC_ARROW_SCALE).min(MAX_ACC_ARROW_LENGTH);
+                let arrow = obj.debug_acc.normalized() * length;
                 painter.arrow(
-                    egui::pos2(player.pos.x, player.pos.y),
-                    end_pos.to_vec2() - egui::pos2(player.pos.x, player.pos.y).to_vec2(),
-                    egui::Stroke::new(3.0, egui::Color32::YELLOW),
+                    egui::pos2(obj.pos.x, obj.pos.y),
+                    egui::vec2(arrow.x, arrow.y),
+                    egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 80, 255)),
                 );
             }
         }
+
+        // Draw floating collision values
+        for label in &self.collision_labels {
+            let alpha = (label.life / COLLISION_LABEL_LIFETIME).clamp(0.0, 1.0);
+            painter.text(
+                egui::pos2(label.pos.x, label.pos.y - (1.0 - alpha) * 20.0),
+                egui::Align2::CENTER_BOTTOM,
+                &label.text,
+                egui::FontId::proportional(14.0),
+                egui::Color32::WHITE.gamma_multiply(alpha),
+            );
+        }
+
+        // Plunger behind the player: the head slides back as it's pulled, squeezing the spring
+        if self.plunger_mode && matches!(self.game_state, GameState::Planning)
+            && let Some(player) = self.objects.iter().find(|o| o.is_player) {
+            let axis = Self::plunger_axis(player);
+            let side = axis.perp() * 8.0;
+            let head = player.pos - axis * (player.radius + 4.0 + self.plunger_pull);
+            let base = player.pos - axis * (player.radius + 4.0 + PLUNGER_MAX_PULL + 20.0);
+            let to_pos = |v: Vec2| egui::pos2(v.x, v.y);
+            let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(200, 200, 200));
+
+            painter.line_segment([to_pos(head - side), to_pos(head + side)], egui::Stroke::new(4.0, egui::Color32::LIGHT_RED));
+            painter.line_segment([to_pos(base - side), to_pos(base + side)], stroke);
+            const COILS: usize = 8;
+            let mut points = vec![to_pos(head)];
+            for k in 1..COILS {
+                let along = head + (base - head) * (k as f32 / COILS as f32);
+                let offset = if k % 2 == 0 { side } else { side * -1.0 };
+                points.push(to_pos(along + offset));
+            }
+            points.push(to_pos(base));
+            painter.add(egui::Shape::line(points, stroke));
+        }
+
+        // Predicted path, not meaningful under planet gravity
+        if self.show_prediction && !self.orbital_mode && matches!(self.game_state, GameState::Planning) {
+            let prediction = self.predict_trajectory();
+            for point in prediction.path.iter().step_by(3) {
+                painter.circle_filled(egui::pos2(point.x, point.y), 1.5, egui::Color32::from_white_alpha(140));
+            }
+            // Ghost of the player where it first touches something, tied to what it hits
+            if let Some(target) = prediction.first_contact.and_then(|i| self.objects.get(i))
+                && let Some(ghost) = prediction.path.last()
+                && let Some(player) = self.objects.iter().find(|o| o.is_player) {
+                let ghost_pos = egui::pos2(ghost.x, ghost.y);
+                let target_pos = egui::pos2(target.pos.x, target.pos.y);
+                painter.circle_filled(ghost_pos, player.radius, player.color.gamma_multiply(0.3));
+                painter.line_segment([ghost_pos, target_pos], egui::Stroke::new(1.0, egui::Color32::from_white_alpha(140)));
+                painter.circle_stroke(target_pos, target.radius + 4.0, egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 230, 80)));
+            }
+        }
+
+        // Draw velocity arrow for player ball in planning mode
+        if matches!(self.game_state, GameState::Planning)
+            && let Some(player) = self.objects.iter().find(|o| o.is_player) {
+            let arrow_scale = 0.15;
+            let max_arrow_length = 120.0;
+            // Cap the length for fast shots but keep the exact direction
+            let speed = player.vel.length();
+            let length = (speed * arrow_scale).min(max_arrow_length);
+            let arrow = player.vel.normalized() * length;
+            let end_pos = egui::pos2(player.pos.x + arrow.x, player.pos.y + arrow.y);
+
+            // A locked shot only slides along this guide
+            if self.lock_aim_direction {
+                let guide = player.pos + player.vel.normalized() * max_arrow_length;
+                painter.extend(egui::Shape::dashed_line(
+                    &[egui::pos2(player.pos.x, player.pos.y), egui::pos2(guide.x, guide.y)],
+                    egui::Stroke::new(1.0, egui::Color32::YELLOW.gamma_multiply(0.5)),
+                    6.0,
+                    4.0,
+                ));
+            }
+            
+            painter.arrow(
+                egui::pos2(player.pos.x, player.pos.y),
+                egui::vec2(arrow.x, arrow.y),
+                egui::Stroke::new(3.0, egui::Color32::YELLOW),
+            );
+            painter.text(
+                end_pos + egui::vec2(arrow.x, arrow.y).normalized() * 8.0,
+                egui::Align2::LEFT_BOTTOM,
+                format!("{speed:.0} px/s"),
+                egui::FontId::proportional(13.0),
+                egui::Color32::YELLOW,
+            );
+        }
+
+        // Fade to black and back while changing levels
+        if let Some(progress) = self.transition {
+            let opacity = 1.0 - (progress - 1.0).abs();
+            painter.rect_filled(self.canvas_rect, 0.0, egui::Color32::from_black_alpha((opacity * 255.0) as u8));
+        }
     }
 }
 
 impl eframe::App for PhysicsApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let now = Instant::now();
-        let dt = (now - self.last_time).as_secs_f32().min(0.016);
+        let frame_time = (now - self.last_time).as_secs_f32().min(MAX_FRAME_TIME);
+        let dt = frame_time * self.time_scale;
         self.last_time = now;
+        self.shake = (self.shake - dt).max(0.0);
+        self.advance_transition(frame_time);
 
-        // Check for level progression
-        if let Some(win_time) = self.win_time {
-            if now.duration_since(win_time).as_secs_f32() > 2.0 {
-                if self.level < 5 {
-                    self.level += 1;
-                    self.setup_level(self.level);
-                }else{
-                    self.game_state = GameState::Won;
-                }
+        if ctx.input(|i| i.key_pressed(egui::Key::Backtick)) {
+            self.console_open = !self.console_open;
+        }
+        if self.console_open {
+            self.show_console(ctx);
+        }
+        if self.lesson_mode {
+            self.show_lesson(ctx);
+        }
+
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.any_down()) {
+            self.idle_since = now;
+            self.attract = None;
+        } else if self.attract.is_none()
+            && matches!(self.game_state, GameState::Planning)
+            && (now - self.idle_since).as_secs_f32() > ATTRACT_IDLE_TIME {
+            self.attract = PhysicsApp::attract_demo();
+        }
+
+        // Check for level progression, practice mode waits for the Next Level button
+        if !self.practice_mode
+            && let Some(win_time) = self.win_time
+            && self.clock - win_time > 2.0 {
+            if self.has_next_level() {
+                self.change_level(self.level + 1);
+            }else{
+                self.game_state = GameState::Won;
             }
         }
 
@@ -902,43 +4460,226 @@ impl eframe::App for PhysicsApp {
             .resizable(false)
             .exact_width(200.0)
             .show(ctx, |ui| {
+                if self.transition.is_some() {
+                    ui.disable();
+                }
                 ui.heading(format!("Level {}", self.level));
+                if let Some(best) = self.best_medals.get(&self.level) {
+                    ui.colored_label(best.color(), format!("Best medal: {}", best.name()));
+                }
+                if self.endless_mode && self.level > LEVEL_COUNT {
+                    ui.label(format!("Endless seed {}", self.endless_seed));
+                }
                 ui.separator();
                 
-                ui.label(format!("Walls: {}/{}", self.count_user_walls(), self.max_walls));
+                if self.no_wall_challenge {
+                    ui.colored_label(egui::Color32::LIGHT_RED, "Walls disabled (challenge)");
+                    ui.label(format!("No-wall wins: {}", self.no_wall_wins));
+                } else {
+                    // One token per wall in the budget, hollow once that wall is placed
+                    let remaining = self.wall_budget().saturating_sub(self.count_user_walls());
+                    ui.horizontal(|ui| {
+                        ui.label("Walls:");
+                        for i in 0..self.wall_budget() {
+                            let (rect, _) = ui.allocate_exact_size(egui::vec2(14.0, 14.0), egui::Sense::hover());
+                            let color = egui::Color32::from_rgb(100, 200, 255);
+                            if i < remaining {
+                                ui.painter().circle_filled(rect.center(), 6.0, color);
+                            } else {
+                                ui.painter().circle_stroke(rect.center(), 5.0, egui::Stroke::new(1.5, color));
+                            }
+                        }
+                    });
+                }
+                if self.combo_mode {
+                    ui.label(format!("Bonus: {}  Combo: x{}", self.bonus_score, self.combo_count));
+                }
                 ui.add_space(10.0);
                 
                 match self.game_state {
                     GameState::Planning => {
                         ui.label("Planning Phase");
                         ui.add_space(5.0);
-                        ui.label("Click and drag to place walls");
+                        ui.label(if self.no_wall_challenge {
+                            "Solve it with the shot alone"
+                        } else {
+                            "Click and drag to place walls"
+                        });
+                        ui.label(if self.lock_aim_direction {
+                            "Drag back from your ball to set its power"
+                        } else {
+                            "Drag back from your ball to aim"
+                        });
                         ui.add_space(10.0);
 
-                        if ui.button("go back").clicked() {
-                            if self.level > 1 {
-                                self.level -= 1;
-                                self.setup_level(self.level);
+                        if ui.checkbox(&mut self.no_wall_challenge, "Challenge: solve with no walls").changed()
+                            && self.no_wall_challenge {
+                            // Not undoable, undo could otherwise bring walls back into the challenge
+                            self.walls.retain(|w| !w.is_user_placed);
+                            self.undo_stack.clear();
+                            self.redo_stack.clear();
+                            if !self.wins_with(&[]) {
+                                self.status_message = Some("This level can't be won without walls".to_string());
+                            }
+                        }
+
+                        ui.checkbox(&mut self.measure_mode, "Measure tool");
+                        ui.checkbox(&mut self.editor_mode, "Editor mode");
+                        if self.editor_mode {
+                            ui.label("Hold F and click a ball to pin/unpin it");
+                            ui.label("Hold R and click a ball to make it repulsive");
+                            ui.label("Hold P and click a ball to make it a planet");
+                            ui.label("Hold K and click a ball to make it breakable");
+                            ui.label("Scroll over a ball to resize it");
+                            ui.label("Drag a yellow handle to move a spring anchor");
+                            ui.label("Hold J and click to drop a jelly ball");
+                            ui.label("Hold V and click to drop a gravity flip zone");
+                            ui.label("Hold S and click to drop a switch for the nearest wall");
+                            ui.label("Hold X and click a ball to delete it");
+                            ui.label("Hold B and click to drop:");
+                            ui.horizontal_wrapped(|ui| {
+                                ui.selectable_value(&mut self.palette, None, "Custom");
+                                for &kind in self.allowed_archetypes {
+                                    ui.selectable_value(&mut self.palette, Some(kind), kind.name());
+                                }
+                            });
+                            if self.palette.is_none() {
+                                ui.add(egui::Slider::new(&mut self.next_ball.radius, 5.0..=60.0).text("Radius"));
+                                let mut from_density = self.next_ball.density.is_some();
+                                if ui.checkbox(&mut from_density, "Mass from size").changed() {
+                                    self.next_ball.density = from_density.then_some(DEFAULT_DENSITY);
+                                }
+                                if let Some(density) = &mut self.next_ball.density {
+                                    ui.add(egui::Slider::new(density, 0.0001..=0.01).logarithmic(true).text("Density"));
+                                    let area = std::f32::consts::PI * self.next_ball.radius * self.next_ball.radius;
+                                    ui.label(format!("Mass: {:.2}", *density * area));
+                                } else {
+                                    ui.add(egui::Slider::new(&mut self.next_ball.mass, 0.1..=20.0).text("Mass"));
+                                }
+                                ui.add(egui::Slider::new(&mut self.next_ball.bounciness, 0.0..=1.0).text("Bounciness"));
                             }
+                            ui.checkbox(&mut self.orbital_mode, "Orbital gravity");
+                            ui.add(egui::Slider::new(&mut self.contact_damping, 0.0..=1.0).text("Wall damping"));
+                            egui::ComboBox::from_label("Boundary")
+                                .selected_text(match self.boundary_mode {
+                                    BoundaryMode::ClosedBox => "Closed box",
+                                    BoundaryMode::OpenTop => "Open top",
+                                    BoundaryMode::WrapAround => "Wrap around",
+                                    BoundaryMode::BottomlessPit => "Bottomless pit",
+                                })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.boundary_mode, BoundaryMode::ClosedBox, "Closed box");
+                                    ui.selectable_value(&mut self.boundary_mode, BoundaryMode::OpenTop, "Open top");
+                                    ui.selectable_value(&mut self.boundary_mode, BoundaryMode::WrapAround, "Wrap around");
+                                    ui.selectable_value(&mut self.boundary_mode, BoundaryMode::BottomlessPit, "Bottomless pit");
+                                });
                         }
-                        if ui.button("go forward").clicked() {
-                            if self.level < 4 {
-                                self.level += 1;
-                                self.setup_level(self.level);
+                        ui.add_space(10.0);
+
+                        ui.collapsing("Level select", |ui| {
+                            if self.thumbnails.is_empty() {
+                                self.thumbnails = (1..=LEVEL_COUNT).map(|level| {
+                                    let mut preview = PhysicsApp { level, ..Default::default() };
+                                    preview.setup_level(level);
+                                    preview.render_thumbnail(ui.ctx(), [80, 60])
+                                }).collect();
+                            }
+                            let mut chosen = None;
+                            egui::Grid::new("level_select").show(ui, |ui| {
+                                for (i, thumbnail) in self.thumbnails.iter().enumerate() {
+                                    let image = egui::Image::new(thumbnail).fit_to_exact_size(egui::vec2(80.0, 60.0));
+                                    if ui.add(egui::Button::image(image)).on_hover_text(format!("Level {}", i + 1)).clicked() {
+                                        chosen = Some(i as u32 + 1);
+                                    }
+                                    if i % 2 == 1 {
+                                        ui.end_row();
+                                    }
+                                }
+                            });
+                            if let Some(level) = chosen {
+                                self.change_level(level);
                             }
+                        });
+
+                        if ui.button("go back").clicked() && self.level > 1 {
+                            self.change_level(self.level - 1);
+                        }
+                        if ui.button("go forward").clicked() && self.has_next_level() {
+                            self.change_level(self.level + 1);
                         }
                         
+                        ui.checkbox(&mut self.plunger_mode, "Plunger launch (hold Space)");
+                        if self.plunger_mode && self.plunger_pull > 0.0 {
+                            ui.label(format!("Pull: {:.0}/{PLUNGER_MAX_PULL:.0}", self.plunger_pull));
+                        }
+
                         if ui.button("Launch Ball").clicked() {
-                            self.game_state = GameState::Simulating;
+                            self.launch();
                         }
                         
+                        ui.collapsing("Place wall by coordinates", |ui| {
+                            let area = self.play_area();
+                            for (name, point) in [("Start", &mut self.typed_wall.0), ("End", &mut self.typed_wall.1)] {
+                                ui.horizontal(|ui| {
+                                    ui.label(name);
+                                    ui.add(egui::DragValue::new(&mut point.x).prefix("x: ").range(area.min.x..=area.max.x));
+                                    ui.add(egui::DragValue::new(&mut point.y).prefix("y: ").range(area.min.y..=area.max.y));
+                                });
+                            }
+                            if ui.button("Add Wall").clicked() {
+                                let (start, end) = self.typed_wall;
+                                self.status_message = self.try_add_user_wall(start, end).err().map(str::to_string);
+                            }
+                        });
+
                         ui.add_space(10.0);
-                        if ui.button("Clear User Walls").clicked() {
-                            self.walls.retain(|w| !w.is_user_placed);
+                        let label = if is_armed(self.clear_armed_at) { "Click again to clear" } else { "Clear User Walls" };
+                        if ui.button(label).clicked() && confirm_click(self.count_user_walls() > 0, &mut self.clear_armed_at) {
+                            self.clear_user_walls();
+                        }
+                        if ui.button(if self.hint_shown.is_some() { "Next Hint" } else { "Hint" }).clicked() {
+                            self.next_hint();
                         }
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(!self.undo_stack.is_empty(), egui::Button::new("Undo")).clicked() {
+                                self.undo();
+                            }
+                            if ui.add_enabled(!self.redo_stack.is_empty(), egui::Button::new("Redo")).clicked() {
+                                self.redo();
+                            }
+                        });
                     }
                     GameState::Simulating => {
-                        ui.label("Simulating...");
+                        ui.label(if self.paused { "Paused" } else { "Simulating..." });
+                        if !self.deferred_pairs.is_empty() {
+                            ui.colored_label(
+                                egui::Color32::LIGHT_RED,
+                                format!("⚠ Pair budget hit, {} contacts deferred", self.deferred_pairs.len()),
+                            );
+                        }
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            if ui.button(if self.paused { "▶ Resume" } else { "⏸ Pause" }).clicked() {
+                                self.paused = !self.paused;
+                            }
+                            if self.paused && ui.button("Step").clicked() {
+                                self.step_frame();
+                            }
+                        });
+                        ui.checkbox(&mut self.break_on_collision, "Break on next collision");
+                        ui.checkbox(&mut self.lesson_mode, "Physics lesson on each hit");
+                        ui.horizontal(|ui| {
+                            if ui.button("Set Checkpoint").clicked() {
+                                self.checkpoint = Some(self.snapshot());
+                            }
+                            if let Some(checkpoint) = &self.checkpoint
+                                && ui.button("Restore Checkpoint").clicked() {
+                                self.restore(checkpoint.clone());
+                            }
+                        });
+                        let recorded = self.history.front().map_or(0.0, |s| self.sim_time - s.time);
+                        ui.label(format!("Hold ← to rewind ({recorded:.1}s recorded)"));
                         ui.add_space(10.0);
                         
                         if ui.button("Reset & Retry").clicked() {
@@ -947,26 +4688,185 @@ impl eframe::App for PhysicsApp {
                     }
                     GameState::Won => {
                         ui.label("🎉 Level Complete!");
+                        ui.label(format!("Won in {} steps ({:.1}s sim)", self.win_steps, self.win_sim_time));
+                        match self.winning_hit {
+                            Some((hitter, Some(player))) => {
+                                ui.label(format!("Solved by player ball #{player} via ball #{hitter}"));
+                            }
+                            Some((hitter, None)) => {
+                                ui.label(format!("Solved by ball #{hitter}, moved by the level itself"));
+                            }
+                            None => {}
+                        }
+                        if let Some(best) = self.best_steps.get(&self.level) {
+                            ui.label(format!("Best: {best} steps"));
+                        }
                         ui.add_space(10.0);
+
+                        if let Some(medal) = self.last_medal {
+                            // Ease-out scale-in driven by the time since the win
+                            let elapsed = self.win_time.map_or(MEDAL_TWEEN_TIME, |t| self.clock - t);
+                            let t = (elapsed / MEDAL_TWEEN_TIME).min(1.0);
+                            let scale = 1.0 - (1.0 - t).powi(3);
+
+                            ui.horizontal(|ui| {
+                                let (rect, _) = ui.allocate_exact_size(egui::vec2(40.0, 40.0), egui::Sense::hover());
+                                ui.painter().circle_filled(rect.center(), 18.0 * scale, medal.color());
+                                ui.painter().circle_stroke(rect.center(), 18.0 * scale, egui::Stroke::new(2.0, egui::Color32::WHITE));
+                                ui.label(format!("{} ({:.2}s)", medal.name(), self.win_sim_time));
+                            });
+                            ui.add_space(10.0);
+                        }
                         
-                        if self.level < 5 {
-                            ui.label("Loading next level...");
+                        if self.has_next_level() {
+                            if self.practice_mode {
+                                if ui.button("Reset & Retry").clicked() {
+                                    self.reset_simulation();
+                                }
+                                if ui.button("Next Level").clicked() {
+                                    self.change_level(self.level + 1);
+                                }
+                            } else {
+                                ui.label("Loading next level...");
+                            }
                         } else {
                             ui.label("All levels complete!");
+                            ui.add_space(5.0);
+
+                            let (mut total_walls, mut total_time) = (0, 0.0);
+                            egui::Grid::new("run_summary").striped(true).show(ui, |ui| {
+                                for header in ["Lvl", "Tries", "Walls", "Time", "Medal"] {
+                                    ui.strong(header);
+                                }
+                                ui.end_row();
+                                for level in 1..=LEVEL_COUNT {
+                                    let stats = self.level_stats.get(&level).cloned().unwrap_or_default();
+                                    ui.label(level.to_string());
+                                    ui.label(stats.attempts.to_string());
+                                    ui.label(stats.walls_used.to_string());
+                                    ui.label(format!("{:.1}s", stats.win_time));
+                                    match self.best_medals.get(&level) {
+                                        Some(medal) => ui.colored_label(medal.color(), medal.name()),
+                                        None => ui.label("-"),
+                                    };
+                                    ui.end_row();
+                                    total_walls += stats.walls_used;
+                                    total_time += stats.win_time;
+                                }
+                                ui.strong("All");
+                                ui.label("");
+                                ui.label(total_walls.to_string());
+                                ui.label(format!("{total_time:.1}s"));
+                                ui.end_row();
+                            });
+                            ui.add_space(5.0);
+
+                            if ui.button("Share Results").clicked() {
+                                ui.ctx().copy_text(self.results_summary());
+                                self.status_message = Some("Results copied to the clipboard".to_string());
+                            }
                             if ui.button("Play Again").clicked() {
-                                self.level = 1;
-                                self.setup_level(1);
+                                self.level_stats.clear();
+                                self.change_level(1);
                             }
                         }
                     }
                 }
-                
-                ui.add_space(20.0);
-                ui.separator();
-                ui.add_space(10.0);
-                
-                if ui.button("Restart Level").clicked() {
-                    self.setup_level(self.level);
+                
+                ui.add_space(20.0);
+                ui.separator();
+                ui.add_space(10.0);
+                
+                let label = if is_armed(self.restart_armed_at) { "Click again to restart" } else { "Restart Level" };
+                if ui.button(label).clicked() && confirm_click(self.count_user_walls() > 0, &mut self.restart_armed_at) {
+                    self.change_level(self.level);
+                }
+
+                ui.add_space(10.0);
+                ui.collapsing("Settings", |ui| {
+                    ui.checkbox(&mut self.show_collision_values, "Show collision impulses");
+                    ui.checkbox(&mut self.show_prediction, "Show predicted path");
+                    ui.checkbox(&mut self.debug_draw, "Debug draw (acceleration)");
+                    ui.checkbox(&mut self.scale_to_fit, "Scale to fit window");
+                    ui.checkbox(&mut self.screen_shake, "Screen shake on big hits");
+                    ui.add(egui::Slider::new(&mut self.max_objects, 20..=1000).text("Ball limit"));
+                    ui.add(egui::Slider::new(&mut self.pair_budget, 10..=5000).logarithmic(true).text("Pair budget"));
+                    ui.add(egui::Slider::new(&mut self.max_speed, 500.0..=10000.0).logarithmic(true).text("Max speed"));
+                    ui.checkbox(&mut self.endless_mode, "Endless mode");
+                    ui.checkbox(&mut self.record_trajectory, "Record trajectory to CSV");
+                    ui.checkbox(&mut self.combo_mode, "Combo bonus mode");
+                    ui.checkbox(&mut self.allow_sleeping, "Sleep resting objects");
+                    ui.checkbox(&mut self.freeze_on_win, "Freeze on win");
+                    ui.checkbox(&mut self.practice_mode, "Practice mode (no auto-advance)");
+                    if self.ball_texture.is_some()
+                        && ui.checkbox(&mut self.textured_player, "Textured player ball").changed() {
+                        self.apply_player_texture();
+                    }
+                    ui.checkbox(&mut self.leading_hemisphere_walls, "Walls ignore balls leaving them");
+                    let mut preset = self.preset;
+                    egui::ComboBox::from_label("Physics preset")
+                        .selected_text(preset.name())
+                        .show_ui(ui, |ui| {
+                            for option in [Preset::Fast, Preset::Balanced, Preset::Accurate] {
+                                ui.selectable_value(&mut preset, option, option.name());
+                            }
+                        });
+                    if preset != self.preset {
+                        self.apply_preset(preset);
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Solver iterations:");
+                        ui.add(egui::DragValue::new(&mut self.solver_iterations).range(1..=16));
+                    });
+                    ui.add(egui::Slider::new(&mut self.separation_stiffness, 0.05..=1.0).text("Push-out stiffness"));
+                    ui.horizontal(|ui| {
+                        ui.label("Air drag:");
+                        ui.add(egui::DragValue::new(&mut self.drag_coefficient).speed(0.00001).range(0.0..=0.001).fixed_decimals(5));
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Rest below (px/s):");
+                        ui.add(egui::DragValue::new(&mut self.restitution_floor).range(0.0..=100.0));
+                    });
+                    if self.endless_mode {
+                        ui.horizontal(|ui| {
+                            ui.label("Seed:");
+                            ui.add(egui::DragValue::new(&mut self.endless_seed));
+                        });
+                    }
+                    ui.checkbox(&mut self.start_jitter, "Jitter level starts");
+                    if self.start_jitter {
+                        ui.horizontal(|ui| {
+                            ui.label("Jitter seed:");
+                            if ui.add(egui::DragValue::new(&mut self.jitter_seed)).changed() {
+                                self.jitter_rng = StdRng::seed_from_u64(self.jitter_seed);
+                            }
+                        });
+                    }
+                });
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if ui.button("Save Game").clicked() {
+                        self.status_message = Some(match self.save_game() {
+                            Ok(()) => format!("Saved to {SAVEGAME_PATH}"),
+                            Err(e) => format!("Save failed: {e}"),
+                        });
+                    }
+                    if ui.button("Load Game").clicked() {
+                        self.status_message = Some(match self.load_game() {
+                            Ok(()) => "Game loaded".to_string(),
+                            Err(e) => format!("Load failed: {e}"),
+                        });
+                    }
+                });
+                if ui.button("Export SVG").clicked() {
+                    self.status_message = Some(match std::fs::write(SVG_PATH, self.scene_svg()) {
+                        Ok(()) => format!("Wrote the layout to {SVG_PATH}"),
+                        Err(e) => format!("SVG export failed: {e}"),
+                    });
+                }
+                if let Some(message) = &self.status_message {
+                    ui.label(message);
                 }
                 
                 ui.add_space(20.0);
@@ -981,42 +4881,1120 @@ impl eframe::App for PhysicsApp {
                 .show(ui, |ui| {
 
                     let rect = ui.available_rect_before_wrap();
-                    self.canvas_rect = rect;
-                    self.bounds = (rect.width(), rect.height());
+                    if self.scale_to_fit {
+                        // Uniform scale so the designed area fits, letterboxed and centered
+                        let (w, h) = DESIGN_BOUNDS;
+                        let design = egui::Rect::from_min_size(egui::pos2(BOARDER_START, 0.0), egui::vec2(w, h));
+                        let scale = (rect.width() / w).min(rect.height() / h);
+                        let offset = rect.center() - design.center() * scale;
+                        self.world_transform = egui::emath::TSTransform::new(offset, scale);
+                        self.canvas_rect = design;
+                        self.bounds = DESIGN_BOUNDS;
+                    } else {
+                        self.world_transform = egui::emath::TSTransform::IDENTITY;
+                        self.canvas_rect = rect;
+                        self.bounds = (rect.width(), rect.height());
+                    }
+
+                    if self.plunger_mode && matches!(self.game_state, GameState::Planning) && self.transition.is_none() {
+                        let held = !ctx.wants_keyboard_input() && ui.input(|i| i.key_down(egui::Key::Space));
+                        self.update_plunger(held, dt);
+                    }
 
                     // Handle mouse input for wall placement
-                    if matches!(self.game_state, GameState::Planning) {
-                        if let Some(pos) = ui.input(|i| i.pointer.hover_pos()) {
-                            let mouse_pos = Vec2::new(pos.x, pos.y);
+                    if matches!(self.game_state, GameState::Planning)
+                        && self.transition.is_none()
+                        && let Some(pos) = self.world_pointer(ui) {
+                        let mouse_pos = Vec2::new(pos.x, pos.y);
+                        let pin_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::F));
+                        let repulsive_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::R));
+                        let planet_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::P));
+                        let breakable_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::K));
+                        let jelly_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::J));
+                        let flip_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::V));
+                        let switch_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::S));
+                        let ball_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::B));
+                        let delete_modifier = self.editor_mode && ui.input(|i| i.key_down(egui::Key::X));
+
+                        // Most specific shortcut first, Ctrl+Z also matches with Shift held
+                        if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)) {
+                            self.redo();
+                        } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z)) {
+                            self.undo();
+                        }
 
+                        // Scrolling over a ball in the editor resizes it, mass follows the area
+                        let scroll = ui.input(|i| i.raw_scroll_delta.y);
+                        if self.editor_mode && scroll != 0.0
+                            && let Some(index) = self.get_object_at_pos(mouse_pos) {
+                            let obj = &self.objects[index];
+                            let radius = (obj.radius * (1.0 + scroll * RESIZE_PER_SCROLL)).clamp(MIN_EDIT_RADIUS, MAX_EDIT_RADIUS);
+                            let mass = obj.mass * (radius / obj.radius).powi(2);
+                            self.edit(EditorCommand::Resize { index, radius, mass });
+                        }
+
+                        if self.measure_mode {
                             if ui.input(|i| i.pointer.primary_pressed()) {
-                                if self.count_user_walls() < self.max_walls  && mouse_pos.x > 210.0 {
-                                    self.placing_wall = Some(mouse_pos);
+                                self.measure_start = Some(mouse_pos);
+                            }
+                            if ui.input(|i| i.pointer.primary_released()) {
+                                self.measure_start = None;
+                            }
+                        } else if pin_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            if let Some(idx) = self.get_object_at_pos(mouse_pos) {
+                                self.edit(EditorCommand::TogglePinned(idx));
+                            }
+                        } else if repulsive_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            if let Some(idx) = self.get_object_at_pos(mouse_pos) {
+                                self.edit(EditorCommand::ToggleRepulsive(idx));
+                            }
+                        } else if planet_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            if let Some(idx) = self.get_object_at_pos(mouse_pos) {
+                                self.edit(EditorCommand::TogglePlanet(idx));
+                            }
+                        } else if breakable_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            if let Some(idx) = self.get_object_at_pos(mouse_pos) {
+                                self.edit(EditorCommand::ToggleBreakable(idx));
+                            }
+                        } else if jelly_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            // The ring plus its center, jelly is never pooled
+                            if self.objects.len() + 13 > self.max_objects {
+                                self.status_message = Some("Ball limit reached".to_string());
+                            } else {
+                                self.edit(EditorCommand::AddSoftBody { center: mouse_pos, radius: 40.0, points: 12 });
+                            }
+                        } else if flip_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            let half = FLIP_ZONE_SIZE * 0.5;
+                            let zone = Aabb::from_points(mouse_pos - half, mouse_pos + half);
+                            self.edit(EditorCommand::AddFlipZone { index: self.flip_zones.len(), zone });
+                        } else if switch_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            match self.nearest_level_wall(mouse_pos) {
+                                Some(target) => self.edit(EditorCommand::AddSwitch { index: self.switches.len(), pos: mouse_pos, target }),
+                                None => self.status_message = Some("A switch needs a level wall to act as its door".to_string()),
+                            }
+                        } else if ball_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            self.status_message = self.spawn_ball(mouse_pos).err().map(str::to_string);
+                        } else if delete_modifier && ui.input(|i| i.pointer.primary_pressed()) {
+                            if let Some(index) = self.get_object_at_pos(mouse_pos) {
+                                // Undo can't rebuild a jelly's ring, everything else comes back
+                                if self.in_soft_body(index) {
+                                    self.status_message = Some("Jelly balls can't be deleted".to_string());
+                                } else {
+                                    let object = self.objects[index].clone();
+                                    self.edit(EditorCommand::RemoveObject { index, object });
                                 }
                             }
+                        } else if self.editor_mode && ui.input(|i| i.pointer.primary_pressed())
+                            && let Some(spring) = self.get_anchor_at_pos(mouse_pos) {
+                            self.dragging_anchor = Some((spring, self.springs[spring].anchor_pos));
+                        } else if ui.input(|i| i.pointer.primary_pressed())
+                            && self.get_object_at_pos(mouse_pos).is_some_and(|i| self.objects[i].is_player) {
+                            self.aiming = true;
+                        } else if ui.input(|i| i.pointer.primary_pressed())
+                            && self.count_user_walls() < self.wall_budget() && self.play_area().contains_point(mouse_pos) {
+                            self.placing_wall = Some(mouse_pos);
+                        }
 
-                            if let Some(start) = self.placing_wall {
-                                if ui.input(|i| i.pointer.primary_released()) {
-                                    // Only add wall if it's long enough
-                                    let length = (mouse_pos - start).length();
-                                    if length > 20.0 {
-                                        self.walls.push(Wall {
-                                            start,
-                                            end: mouse_pos,
-                                            is_user_placed: true,
-                                        });
-                                    }
-                                    self.placing_wall = None;
-                                }
+                        // The anchor follows the mouse live, release records one undoable move
+                        if let Some((spring, from)) = self.dragging_anchor {
+                            self.move_anchor(spring, mouse_pos);
+                            if ui.input(|i| i.pointer.primary_released()) {
+                                self.move_anchor(spring, from);
+                                self.edit(EditorCommand::MoveAnchor { spring, pos: mouse_pos });
+                                self.dragging_anchor = None;
+                            }
+                        }
+
+                        if self.aiming {
+                            self.aim_player(mouse_pos);
+                            if ui.input(|i| i.pointer.primary_released()) {
+                                self.aiming = false;
                             }
                         }
+
+                        if let Some(start) = self.placing_wall
+                            && ui.input(|i| i.pointer.primary_released()) {
+                            // A too-short drag is just a click, ignore it quietly
+                            let _ = self.try_add_user_wall(start, mouse_pos);
+                            self.placing_wall = None;
+                        }
                     }
                     
-                    self.update_physics(dt);
-                    self.render(ui);
+                    if matches!(self.game_state, GameState::Simulating)
+                        && !ctx.wants_keyboard_input()
+                        && ui.input(|i| i.key_down(egui::Key::ArrowLeft)) {
+                        self.rewind();
+                    }
+
+                    self.advance_physics(dt);
+
+                    // The attempt ended (won or reset), flush the recorded trajectory
+                    if self.record_trajectory && !self.trajectory.is_empty()
+                        && !matches!(self.game_state, GameState::Simulating) {
+                        self.finish_trajectory();
+                    }
+
+                    let world_clip = self.world_transform.inverse() * ui.clip_rect();
+                    // Shake only moves the picture, physics and input stay put
+                    let mut view = self.world_transform;
+                    if self.screen_shake && self.shake > 0.0 {
+                        let amplitude = SHAKE_AMPLITUDE * self.shake / SHAKE_TIME;
+                        let mut rng = rand::rng();
+                        view = egui::emath::TSTransform::from_translation(egui::vec2(
+                            rng.random_range(-amplitude..=amplitude),
+                            rng.random_range(-amplitude..=amplitude),
+                        )) * view;
+                    }
+                    if let Some(demo) = &mut self.attract {
+                        // Loop the demo a moment after it wins
+                        if demo.win_time.is_some_and(|t| demo.clock - t > 2.0)
+                            || matches!(demo.game_state, GameState::Planning) {
+                            self.attract = PhysicsApp::attract_demo();
+                        }
+                    }
+                    if let Some(demo) = &mut self.attract {
+                        demo.canvas_rect = self.canvas_rect;
+                        demo.bounds = self.bounds;
+                        demo.advance_physics(dt);
+                    }
+                    ui.with_visual_transform(view, |ui| {
+                        ui.set_clip_rect(world_clip);
+                        match &self.attract {
+                            Some(demo) => {
+                                demo.render(ui);
+                                ui.painter().text(
+                                    self.canvas_rect.center_top() + egui::vec2(0.0, 30.0),
+                                    egui::Align2::CENTER_CENTER,
+                                    "DEMO - move the mouse to play",
+                                    egui::FontId::proportional(24.0),
+                                    egui::Color32::WHITE,
+                                );
+                            }
+                            None => self.render(ui),
+                        }
+                    });
                 });
         });
 
         ctx.request_repaint();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A ball at rest, built the way the levels build theirs
+    fn ball(pos: Vec2, radius: f32) -> PhysicsObject {
+        PhysicsObject::ball(pos, radius, DEFAULT_DENSITY)
+    }
+
+    // An empty box mid-attempt, for dropping hand-placed objects into
+    fn empty_scene() -> PhysicsApp {
+        let mut app = PhysicsApp::default();
+        app.objects.clear();
+        app.walls.clear();
+        app.springs.clear();
+        app.no_collide_pairs.clear();
+        app.game_state = GameState::Simulating;
+        app
+    }
+
+    fn run(app: &mut PhysicsApp, seconds: f32) {
+        for _ in 0..(seconds / SOLVER_DT).round() as usize {
+            app.update_physics(SOLVER_DT);
+        }
+    }
+
+    #[test]
+    fn spawn_overlap_with_the_goal_does_not_win() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.objects.push(PhysicsObject { is_player: true, ..ball(Vec2::new(300.0, 300.0), 18.0) });
+        app.objects.push(ball(Vec2::new(600.0, 300.0), 20.0));
+        app.objects.push(PhysicsObject { is_goal: true, ..ball(Vec2::new(630.0, 300.0), 20.0) });
+
+        run(&mut app, 1.0);
+        assert!(!app.win_armed);
+        assert!(app.satisfied_goals.is_empty());
+        assert!(!matches!(app.game_state, GameState::Won));
+    }
+
+    #[test]
+    fn tangential_launch_orbits_a_planet() {
+        let mut app = empty_scene();
+        app.orbital_mode = true;
+        let center = Vec2::new(610.0, 300.0);
+        let r = 150.0;
+        app.objects.push(PhysicsObject { mass: 20.0, fixed: true, planet: true, ..ball(center, 20.0) });
+        // Circular orbit speed for the softened pull at this radius, v² = a r
+        let pull = 5.0e5 * 20.0 * r / (r * r + 20.0 * 20.0_f32).powf(1.5);
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(0.0, -(pull * r).sqrt()),
+            ..ball(center + Vec2::new(r, 0.0), 5.0)
+        });
+
+        let mut swept = 0.0;
+        let mut last = app.objects[1].pos - center;
+        for _ in 0..240 {
+            app.update_physics(SOLVER_DT);
+            let now = app.objects[1].pos - center;
+            assert!((now.length() - r).abs() < 0.1 * r, "left the orbit at {} px", now.length());
+            swept += (last.x * now.y - last.y * now.x).atan2(last.dot(&now)).abs();
+            last = now;
+        }
+        // Roughly once round the planet in those four seconds
+        assert!(swept > std::f32::consts::PI, "only swept {swept} rad");
+    }
+
+    #[test]
+    fn only_planets_pull_in_orbital_mode() {
+        let mut app = empty_scene();
+        app.orbital_mode = true;
+        // Heavy and pinned, but not a planet
+        app.objects.push(PhysicsObject { mass: 20.0, fixed: true, ..ball(Vec2::new(600.0, 300.0), 20.0) });
+        app.objects.push(ball(Vec2::new(700.0, 300.0), 5.0));
+
+        run(&mut app, 1.0);
+        assert_eq!(app.objects[1].pos, Vec2::new(700.0, 300.0));
+    }
+
+    #[test]
+    fn repulsive_object_deflects_before_contact() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        // A nonsensical mass must not matter to a repulsive object
+        app.objects.push(PhysicsObject {
+            mass: -1.0,
+            repulsive: true,
+            ..ball(Vec2::new(600.0, 300.0), 20.0)
+        });
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(150.0, 0.0),
+            ..ball(Vec2::new(450.0, 300.0), 15.0)
+        });
+
+        for _ in 0..120 {
+            app.update_physics(SOLVER_DT);
+            let (a, b) = (&app.objects[0], &app.objects[1]);
+            let gap = (a.pos - b.pos).length() - a.radius - b.radius;
+            assert!(gap > 0.0, "touched the repulsive ball");
+        }
+        assert!(app.objects[1].vel.x < 0.0);
+        assert!(app.objects.iter().all(|o| o.pos.is_finite() && o.vel.is_finite()));
+    }
+
+    fn wall(start: Vec2, end: Vec2) -> Wall {
+        Wall {
+            start,
+            end,
+            is_user_placed: false,
+            restitution: None,
+            thickness: WALL_THICKNESS,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn hard_hit_dents_a_plastic_ball() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.walls.push(wall(Vec2::new(600.0, 100.0), Vec2::new(600.0, 500.0)));
+        app.objects.push(PhysicsObject {
+            mass: 1.0,
+            vel: Vec2::new(400.0, 0.0),
+            initial_vel: Vec2::new(400.0, 0.0),
+            plasticity: 0.2,
+            ..ball(Vec2::new(500.0, 300.0), 15.0)
+        });
+
+        run(&mut app, 0.5);
+        let ball = &app.objects[0];
+        assert!(ball.vel.x < 0.0, "never reached the wall");
+        assert!((ball.bounciness - 0.6).abs() < 1e-6, "bounciness {}", ball.bounciness);
+
+        app.reset_simulation();
+        assert_eq!(app.objects[0].bounciness, 0.8);
+    }
+
+    #[test]
+    fn dead_wall_stops_the_bounce() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.walls.push(Wall { restitution: Some(0.0), ..wall(Vec2::new(600.0, 100.0), Vec2::new(600.0, 500.0)) });
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(300.0, 0.0),
+            bounciness: 1.0,
+            ..ball(Vec2::new(500.0, 300.0), 15.0)
+        });
+
+        run(&mut app, 0.5);
+        let ball = &app.objects[0];
+        // Stuck against the wall instead of coming back off it
+        assert!(ball.vel.x.abs() < 1e-3, "vel ({}, {})", ball.vel.x, ball.vel.y);
+        assert!(ball.pos.x > 570.0);
+    }
+
+    #[test]
+    fn low_gravity_zone_slows_a_fall() {
+        let drop = |zone: Option<GravityZone>| {
+            let mut app = empty_scene();
+            app.gravity_zones.extend(zone);
+            app.objects.push(ball(Vec2::new(500.0, 100.0), 10.0));
+            run(&mut app, 0.5);
+            app.objects[0].vel.y
+        };
+        let floaty = GravityZone {
+            rect: Aabb::from_points(Vec2::new(400.0, 0.0), Vec2::new(600.0, 400.0)),
+            gravity: Vec2::new(0.0, 50.0),
+        };
+        let normal = drop(None);
+        let slowed = drop(Some(floaty));
+        assert!(slowed > 0.0 && slowed < normal * 0.25, "{slowed} vs {normal}");
+    }
+
+    // Largest overlap between any two balls, 0 when nothing touches
+    fn deepest_overlap(app: &PhysicsApp) -> f32 {
+        let mut deepest: f32 = 0.0;
+        for j in 1..app.objects.len() {
+            for i in 0..j {
+                let (a, b) = (&app.objects[i], &app.objects[j]);
+                deepest = deepest.max(a.radius + b.radius - (a.pos - b.pos).length());
+            }
+        }
+        deepest
+    }
+
+    #[test]
+    fn corner_stack_stays_separated_with_more_iterations() {
+        let mut app = empty_scene();
+        app.solver_iterations = 8;
+        let (right, floor) = (BOARDER_START + app.bounds.0, app.bounds.1);
+        for (i, j) in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (0, 2)] {
+            let pos = Vec2::new(right - 16.0 - 32.0 * i as f32, floor - 16.0 - 32.0 * j as f32);
+            app.objects.push(ball(pos, 15.0));
+        }
+
+        run(&mut app, 3.0);
+        assert!(deepest_overlap(&app) < 0.5, "overlap {}", deepest_overlap(&app));
+    }
+
+    #[test]
+    fn slow_floor_hits_stop_instead_of_bouncing() {
+        let rebound = |speed: f32| {
+            let mut app = empty_scene();
+            app.gravity = Vec2::new(0.0, 0.0);
+            let floor = app.bounds.1;
+            app.objects.push(PhysicsObject {
+                vel: Vec2::new(0.0, speed),
+                bounciness: 1.0,
+                ..ball(Vec2::new(500.0, floor - 15.1), 15.0)
+            });
+            app.update_physics(SOLVER_DT);
+            app.objects[0].vel.y
+        };
+        // Under the 20 px/s floor the hit becomes resting contact, above it the ball rebounds
+        assert_eq!(rebound(15.0), 0.0);
+        assert!((rebound(60.0) + 60.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn balls_in_a_v_corner_settle_without_drifting() {
+        let mut app = empty_scene();
+        let bottom = Vec2::new(600.0, 500.0);
+        app.walls.push(wall(Vec2::new(400.0, 200.0), bottom));
+        app.walls.push(wall(bottom, Vec2::new(800.0, 200.0)));
+        for i in 0..5 {
+            let pos = Vec2::new(590.0 + 5.0 * i as f32, 250.0 + 40.0 * i as f32);
+            app.objects.push(PhysicsObject { bounciness: 0.2, ..ball(pos, 15.0) });
+        }
+
+        run(&mut app, 2.0);
+        let settled: Vec<Vec2> = app.objects.iter().map(|o| o.pos).collect();
+        run(&mut app, 0.4);
+        for (obj, start) in app.objects.iter().zip(&settled) {
+            assert!((obj.pos - *start).length() < 0.5, "drifted {} px", (obj.pos - *start).length());
+            assert!(obj.pos.y < bottom.y, "fell through the corner");
+        }
+        // One solver iteration leaves about a pixel of give under the pile's weight
+        assert!(deepest_overlap(&app) < 2.0, "overlap {}", deepest_overlap(&app));
+    }
+
+    #[test]
+    fn drag_slows_a_bigger_ball_more() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.drag_coefficient = 0.0005;
+        // Same mass and launch, only the cross-section differs
+        for (y, radius) in [(200.0, 5.0), (400.0, 20.0)] {
+            app.objects.push(PhysicsObject {
+                mass: 1.0,
+                vel: Vec2::new(300.0, 0.0),
+                ..ball(Vec2::new(300.0, y), radius)
+            });
+        }
+
+        run(&mut app, 1.0);
+        let (small, big) = (app.objects[0].pos.x - 300.0, app.objects[1].pos.x - 300.0);
+        assert!(big < small * 0.8, "big went {big}, small went {small}");
+    }
+
+    #[test]
+    fn flipped_ball_falls_up_into_a_high_goal() {
+        let attempt = |zone: Option<Aabb>| {
+            let mut app = empty_scene();
+            app.win_armed = true;
+            app.flip_zones.extend(zone);
+            app.objects.push(PhysicsObject { is_goal: true, fixed: true, ..ball(Vec2::new(500.0, 80.0), 20.0) });
+            app.objects.push(ball(Vec2::new(500.0, 200.0), 10.0));
+            run(&mut app, 3.0);
+            matches!(app.game_state, GameState::Won)
+        };
+        // Tall enough that the ball turns round inside it instead of re-entering from below
+        let zone = Aabb::from_points(Vec2::new(400.0, 300.0), Vec2::new(600.0, 500.0));
+        assert!(attempt(Some(zone)));
+        assert!(!attempt(None));
+    }
+
+    #[test]
+    fn wall_leaves_a_ball_that_already_crossed_it() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.walls.push(wall(Vec2::new(600.0, 100.0), Vec2::new(600.0, 500.0)));
+        // Tunnelled halfway through at speed, still heading out the far side
+        app.objects.push(PhysicsObject { vel: Vec2::new(600.0, 0.0), ..ball(Vec2::new(595.0, 300.0), 15.0) });
+
+        app.update_physics(SOLVER_DT);
+        let ball_x = app.objects[0].pos.x;
+        assert_eq!(app.objects[0].vel.x, 600.0);
+        assert!((ball_x - (595.0 + 600.0 * SOLVER_DT)).abs() < 1e-3, "shoved to {ball_x}");
+
+        // A ball heading into the wall still bounces
+        app.objects[0] = PhysicsObject { vel: Vec2::new(600.0, 0.0), ..ball(Vec2::new(580.0, 300.0), 15.0) };
+        app.update_physics(SOLVER_DT);
+        assert!(app.objects[0].vel.x < 0.0);
+        assert!(app.objects[0].pos.x < 600.0);
+    }
+
+    #[test]
+    fn overlapping_spawns_separate_without_gaining_speed() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.objects.push(ball(Vec2::new(500.0, 200.0), 20.0));
+        app.objects.push(ball(Vec2::new(520.0, 200.0), 20.0));
+        // A fixed post stays put and only the ball moves off it
+        app.objects.push(PhysicsObject { fixed: true, ..ball(Vec2::new(500.0, 400.0), 20.0) });
+        app.objects.push(ball(Vec2::new(500.0, 430.0), 20.0));
+        // Pairs that never collide are allowed to overlap
+        app.objects.push(ball(Vec2::new(700.0, 300.0), 20.0));
+        app.objects.push(ball(Vec2::new(710.0, 300.0), 20.0));
+        app.no_collide_pairs.push((4, 5));
+
+        app.resolve_initial_overlaps();
+        assert!(app.objects[0].circle().penetration(&app.objects[1].circle()) < 0.02);
+        assert!(app.objects[2].circle().penetration(&app.objects[3].circle()) < 0.02);
+        assert_eq!(app.objects[2].pos, Vec2::new(500.0, 400.0));
+        assert_eq!(app.objects[4].pos, Vec2::new(700.0, 300.0));
+        assert_eq!(app.objects[5].pos, Vec2::new(710.0, 300.0));
+        // Retries start from the separated layout
+        assert!(app.objects.iter().all(|o| o.initial_pos == o.pos));
+
+        run(&mut app, 0.5);
+        assert!(app.objects.iter().all(|o| o.vel.length() < 1e-3), "something got launched");
+    }
+
+    #[test]
+    fn soft_separation_converges_over_several_steps() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.separation_stiffness = 0.5;
+        app.objects.push(ball(Vec2::new(500.0, 300.0), 20.0));
+        app.objects.push(ball(Vec2::new(530.0, 300.0), 20.0));
+
+        app.update_physics(SOLVER_DT);
+        let first = deepest_overlap(&app);
+        // Only part of the 10px overlap goes in one step
+        assert!(first > 1.0 && first < 9.0, "overlap after one step {first}");
+        run(&mut app, 0.5);
+        assert!(deepest_overlap(&app) < 0.01, "overlap {}", deepest_overlap(&app));
+    }
+
+    #[test]
+    fn sliding_ball_spins_up_to_rolling() {
+        let slide = |radius: f32| {
+            let mut app = empty_scene();
+            let floor = app.bounds.1;
+            app.objects.push(PhysicsObject { vel: Vec2::new(200.0, 0.0), ..ball(Vec2::new(300.0, floor - radius), radius) });
+            run(&mut app, 1.0);
+            app.objects[0].clone()
+        };
+
+        let ball = slide(15.0);
+        // Rolling without slipping, v = ω r
+        assert!((ball.angular_vel * ball.radius - ball.vel.x).abs() < 0.01 * ball.vel.x,
+            "ω r = {}, v = {}", ball.angular_vel * ball.radius, ball.vel.x);
+        // A tiny ball would need more spin than the cap allows
+        assert_eq!(slide(2.0).angular_vel, MAX_ANGULAR_VEL);
+    }
+
+    #[test]
+    fn thick_wall_bounces_at_its_visible_edge() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.walls.push(Wall { thickness: 20.0, ..wall(Vec2::new(600.0, 100.0), Vec2::new(600.0, 500.0)) });
+        app.objects.push(PhysicsObject { vel: Vec2::new(300.0, 0.0), ..ball(Vec2::new(500.0, 300.0), 15.0) });
+
+        let mut closest = 0.0_f32;
+        for _ in 0..60 {
+            app.update_physics(SOLVER_DT);
+            closest = closest.max(app.objects[0].pos.x);
+        }
+        assert!(app.objects[0].vel.x < 0.0, "never bounced");
+        // Edge at 590, so the center turns round a radius short of it rather than of 600
+        assert!((closest - 575.0).abs() < 0.5, "turned round at {closest}");
+    }
+
+    #[test]
+    fn player_passes_through_a_no_collide_goal() {
+        let shoot = |pass_through: bool| {
+            let mut app = empty_scene();
+            app.gravity = Vec2::new(0.0, 0.0);
+            app.objects.push(PhysicsObject {
+                is_player: true,
+                vel: Vec2::new(300.0, 0.0),
+                ..ball(Vec2::new(400.0, 300.0), 15.0)
+            });
+            app.objects.push(PhysicsObject { is_goal: true, ..ball(Vec2::new(500.0, 300.0), 20.0) });
+            if pass_through {
+                app.no_collide_pairs.push((0, 1));
+            }
+            run(&mut app, 1.0);
+            (app.objects[0].pos.x, app.objects[1].pos)
+        };
+
+        let (player_x, goal_pos) = shoot(true);
+        assert!(player_x > 600.0, "player stopped at {player_x}");
+        assert_eq!((goal_pos.x, goal_pos.y), (500.0, 300.0));
+
+        // Without the filter the player knocks the goal along instead
+        let (player_x, goal_pos) = shoot(false);
+        assert!(player_x < 500.0);
+        assert!(goal_pos.x > 500.0);
+    }
+
+    #[test]
+    fn goal_tolerance_forgives_a_near_miss() {
+        let graze = |gap: f32, tolerance: f32| {
+            let mut app = empty_scene();
+            app.gravity = Vec2::new(0.0, 0.0);
+            app.win_armed = true;
+            app.goal_tolerance = tolerance;
+            app.objects.push(PhysicsObject {
+                is_goal: true,
+                fixed: true,
+                ..ball(Vec2::new(500.0, 300.0), 20.0)
+            });
+            // Flies straight past, never closer than `gap` to the goal's edge
+            app.objects.push(PhysicsObject {
+                vel: Vec2::new(200.0, 0.0),
+                ..ball(Vec2::new(400.0, 300.0 - 35.0 - gap), 15.0)
+            });
+            run(&mut app, 1.0);
+            let vel = app.objects[1].vel;
+            assert_eq!((vel.x, vel.y), (200.0, 0.0), "the graze touched physically");
+            matches!(app.game_state, GameState::Won)
+        };
+        assert!(graze(2.0, 3.0));
+        assert!(!graze(3.5, 3.0));
+        assert!(!graze(2.0, 0.0));
+    }
+
+    #[test]
+    fn circle_geometry() {
+        let a = Circle { center: Vec2::new(0.0, 0.0), radius: 10.0 };
+        let near = Circle { center: Vec2::new(15.0, 0.0), radius: 10.0 };
+        let touching = Circle { center: Vec2::new(20.0, 0.0), radius: 10.0 };
+        let far = Circle { center: Vec2::new(30.0, 0.0), radius: 10.0 };
+
+        assert_eq!(a.penetration(&near), 5.0);
+        assert!(a.overlaps(&near) && near.overlaps(&a));
+        // Exactly touching is not overlapping
+        assert_eq!(a.penetration(&touching), 0.0);
+        assert!(!a.overlaps(&touching));
+        assert_eq!(a.penetration(&far), -10.0);
+        assert!(!a.overlaps(&far));
+
+        assert!(a.contains(Vec2::new(6.0, 8.0)));
+        assert!(a.contains(Vec2::new(10.0, 0.0)));
+        assert!(!a.contains(Vec2::new(8.0, 8.0)));
+    }
+
+    #[test]
+    fn shattering_breakable_scatters_its_neighbours() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        let center = Vec2::new(600.0, 300.0);
+        // Already took its last hit, it shatters on the next step
+        app.objects.push(PhysicsObject {
+            fixed: true,
+            health: Some(500.0),
+            damage: 500.0,
+            ..ball(center, 20.0)
+        });
+        let around = [Vec2::new(1.0, 0.0), Vec2::new(-1.0, 0.0), Vec2::new(0.0, 1.0), Vec2::new(0.0, -1.0)];
+        for dir in around {
+            app.objects.push(ball(center + dir * 50.0, 10.0));
+        }
+        // Out of the blast's reach
+        app.objects.push(ball(Vec2::new(300.0, 300.0), 10.0));
+
+        app.update_physics(SOLVER_DT);
+        assert!(app.objects[0].lost);
+        for (obj, dir) in app.objects[1..5].iter().zip(around) {
+            let sideways = obj.vel.x * dir.y - obj.vel.y * dir.x;
+            assert!(obj.vel.dot(&dir) > 0.0 && sideways.abs() < 1e-3, "vel ({}, {})", obj.vel.x, obj.vel.y);
+        }
+        assert_eq!(app.objects[5].vel.length(), 0.0);
+    }
+
+    #[test]
+    fn level_navigation_stays_within_the_registry() {
+        let mut app = PhysicsApp::default();
+        // Drive a level change through its whole fade, the way the buttons do
+        let go_to = |app: &mut PhysicsApp, level: u32| {
+            app.change_level(level);
+            app.advance_transition(TRANSITION_TIME);
+            app.advance_transition(TRANSITION_TIME);
+            assert!(app.transition.is_none());
+            assert!((1..=LEVELS.len() as u32).contains(&app.level), "level {}", app.level);
+            assert!(!app.objects.is_empty(), "level {} built nothing", app.level);
+        };
+
+        assert_eq!(app.level, 1);
+        while app.has_next_level() {
+            let next = app.level + 1;
+            go_to(&mut app, next);
+        }
+        assert_eq!(app.level, LEVELS.len() as u32);
+        while app.level > 1 {
+            let prev = app.level - 1;
+            go_to(&mut app, prev);
+        }
+        assert_eq!(app.level, 1);
+    }
+
+    #[test]
+    fn every_level_reference_solution_wins() {
+        for level in 1..=LEVEL_COUNT {
+            let mut app = PhysicsApp { level, ..Default::default() };
+            app.setup_level(level);
+            let replay = app.reference_solution.clone()
+                .unwrap_or_else(|| panic!("level {level} has no reference solution"));
+            assert!(app.replay_wins(&replay), "level {level}'s reference solution no longer wins");
+        }
+    }
+
+    #[test]
+    fn non_finite_ball_goes_back_to_its_start() {
+        let mut app = empty_scene();
+        let start = Vec2::new(500.0, 200.0);
+        app.objects.push(PhysicsObject {
+            initial_vel: Vec2::new(50.0, 0.0),
+            ..ball(start, 15.0)
+        });
+        app.objects.push(ball(Vec2::new(700.0, 200.0), 15.0));
+        app.objects[0].vel = Vec2::new(f32::NAN, 0.0);
+
+        app.update_physics(SOLVER_DT);
+        let ball = &app.objects[0];
+        assert_eq!((ball.pos.x, ball.pos.y), (start.x, start.y));
+        assert_eq!((ball.vel.x, ball.vel.y), (50.0, 0.0));
+        // Its neighbour is untouched by the recovery
+        assert!(app.objects[1].pos.y > 200.0);
+        assert_eq!(app.nonfinite_resets, 1);
+
+        run(&mut app, 0.5);
+        assert!(app.objects.iter().all(|o| o.pos.is_finite() && o.vel.is_finite()));
+        assert_eq!(app.nonfinite_resets, 1);
+    }
+
+    #[test]
+    fn massless_ball_on_a_spring_stays_finite() {
+        let mut app = empty_scene();
+        app.drag_coefficient = 0.01;
+        app.objects.push(PhysicsObject { mass: 0.0, ..ball(Vec2::new(500.0, 200.0), 10.0) });
+        app.springs.push(Spring {
+            object_index: 0,
+            anchor: None,
+            anchor_pos: Vec2::new(500.0, 100.0),
+            rest_length: 50.0,
+            stiffness: 10.0,
+        });
+
+        run(&mut app, 0.5);
+        assert_eq!(app.nonfinite_resets, 0);
+        assert!(app.objects[0].pos.is_finite() && app.objects[0].vel.is_finite());
+    }
+
+    #[derive(Default)]
+    struct CountingObserver {
+        hits: Vec<(usize, usize)>,
+        wall_hits: Vec<usize>,
+    }
+
+    impl CollisionObserver for CountingObserver {
+        fn on_collision(&mut self, a: usize, b: usize, _impulse: f32) {
+            self.hits.push((a, b));
+        }
+
+        fn on_wall(&mut self, obj: usize, _impulse: f32) {
+            self.wall_hits.push(obj);
+        }
+    }
+
+    #[test]
+    fn observer_hears_each_collision_once() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        let observer = Rc::new(RefCell::new(CountingObserver::default()));
+        app.collision_observer = Some(observer.clone());
+        app.walls.push(wall(Vec2::new(550.0, 350.0), Vec2::new(550.0, 450.0)));
+        // One ball knocks into another, a third bounces off the wall
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(200.0, 0.0),
+            ..ball(Vec2::new(400.0, 200.0), 15.0)
+        });
+        app.objects.push(ball(Vec2::new(500.0, 200.0), 15.0));
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(200.0, 0.0),
+            ..ball(Vec2::new(400.0, 400.0), 15.0)
+        });
+
+        run(&mut app, 1.0);
+        let observer = observer.borrow();
+        assert_eq!(observer.hits, vec![(0, 1)]);
+        assert_eq!(observer.wall_hits, vec![2]);
+    }
+
+    #[test]
+    fn chain_hit_credits_the_player() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        let at = |x: f32, y: f32| ball(Vec2::new(x, y), 15.0);
+        app.objects.push(PhysicsObject { is_player: true, vel: Vec2::new(300.0, 0.0), ..at(300.0, 300.0) });
+        app.objects.push(at(380.0, 300.0));
+        app.objects.push(at(460.0, 300.0));
+        app.objects.push(PhysicsObject { is_goal: true, ..at(560.0, 300.0) });
+        // Knocked by something other than the player, credits nobody
+        app.objects.push(PhysicsObject { vel: Vec2::new(300.0, 0.0), ..at(300.0, 450.0) });
+        app.objects.push(at(380.0, 450.0));
+
+        run(&mut app, 1.5);
+        assert!(matches!(app.game_state, GameState::Won));
+        assert_eq!(app.objects[1].last_touched_by, Some(0));
+        assert_eq!(app.objects[2].last_touched_by, Some(0));
+        assert_eq!(app.winning_hit, Some((2, Some(0))));
+        assert!(app.objects[5].vel.x > 0.0);
+        assert_eq!(app.objects[5].last_touched_by, None);
+    }
+
+    #[test]
+    fn hitting_a_switch_opens_its_door() {
+        let attempt = |press: bool| {
+            let mut app = empty_scene();
+            app.walls.push(wall(Vec2::new(400.0, 300.0), Vec2::new(600.0, 300.0)));
+            app.switches.push(Switch {
+                pos: Vec2::new(300.0, app.bounds.1 - SWITCH_RADIUS),
+                radius: SWITCH_RADIUS,
+                target_wall_indices: vec![0],
+                touched: false,
+                flipped: false,
+            });
+            // Waiting on the door
+            app.objects.push(ball(Vec2::new(500.0, 250.0), 15.0));
+            if press {
+                // Lands dead on the switch and stays there
+                app.objects.push(PhysicsObject {
+                    bounciness: 0.0,
+                    ..ball(Vec2::new(300.0, 250.0), 10.0)
+                });
+            }
+            run(&mut app, 2.0);
+            (app.walls[0].active, app.objects[0].pos.y)
+        };
+
+        let (door_active, ball_y) = attempt(true);
+        assert!(!door_active);
+        assert!(ball_y > 300.0, "still held at {ball_y}");
+        let (door_active, ball_y) = attempt(false);
+        assert!(door_active);
+        assert!(ball_y < 300.0);
+    }
+
+    #[test]
+    fn vec2_cross_and_perp() {
+        let x = Vec2::new(1.0, 0.0);
+        let y = Vec2::new(0.0, 1.0);
+        assert_eq!(x.cross(&y), 1.0);
+        assert_eq!(y.cross(&x), -1.0);
+        assert_eq!(x.cross(&x), 0.0);
+        assert_eq!(x.perp(), Vec2::new(0.0, 1.0));
+        assert_eq!(y.perp(), Vec2::new(-1.0, 0.0));
+
+        let d = Vec2::new(2.0, 3.0);
+        assert_eq!(d.cross(&Vec2::new(-1.0, 4.0)), 11.0);
+        assert_eq!(d.perp(), Vec2::new(-3.0, 2.0));
+        // The perpendicular is square to the original and just as long
+        assert_eq!(d.dot(&d.perp()), 0.0);
+        assert_eq!(d.perp().length(), d.length());
+    }
+
+    #[test]
+    fn vec2_divides_by_scalar() {
+        assert_eq!(Vec2::new(4.0, 2.0) / 2.0, Vec2::new(2.0, 1.0));
+    }
+
+    #[test]
+    fn vec2_distance_of_a_3_4_5_triangle() {
+        let a = Vec2::new(1.0, 2.0);
+        let b = Vec2::new(4.0, 6.0);
+        assert_eq!(a.distance(&b), 5.0);
+        assert_eq!(b.distance(&a), 5.0);
+        assert_eq!(a.distance_squared(&b), 25.0);
+        assert_eq!(a.distance(&a), 0.0);
+    }
+
+    #[test]
+    fn vec2_rotate_and_from_angle() {
+        assert_eq!(Vec2::from_angle(0.0), Vec2::new(1.0, 0.0));
+        let turned = Vec2::new(1.0, 0.0).rotate(std::f32::consts::FRAC_PI_2);
+        assert!(turned.distance(&Vec2::new(0.0, 1.0)) < 1e-6, "{turned:?}");
+        let half = Vec2::new(2.0, 1.0).rotate(std::f32::consts::PI);
+        assert!(half.distance(&Vec2::new(-2.0, -1.0)) < 1e-6, "{half:?}");
+        assert!((Vec2::from_angle(1.234).length() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn vec2_reflect_off_a_floor() {
+        let up = Vec2::new(0.0, 1.0);
+        assert_eq!(Vec2::new(1.0, -1.0).reflect(&up), Vec2::new(1.0, 1.0));
+        // Moving along the surface is left alone
+        assert_eq!(Vec2::new(3.0, 0.0).reflect(&up), Vec2::new(3.0, 0.0));
+    }
+
+    #[test]
+    fn vec2_clamp_length() {
+        let clamped = Vec2::new(3000.0, 4000.0).clamp_length(1000.0);
+        assert!((clamped.length() - 1000.0).abs() < 1e-3);
+        assert!(clamped.distance(&Vec2::new(600.0, 800.0)) < 1e-3);
+        // Shorter vectors come back unchanged
+        assert_eq!(Vec2::new(3.0, 4.0).clamp_length(1000.0), Vec2::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn step_caps_object_speed() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.max_speed = 1000.0;
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(3000.0, 4000.0),
+            ..ball(Vec2::new(500.0, 100.0), 10.0)
+        });
+        app.update_physics(SOLVER_DT);
+        assert!(app.objects[0].vel.length() <= 1000.0 + 1e-3);
+    }
+
+    #[test]
+    fn ceiling_bounce_keeps_balls_on_the_ceiling_line() {
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        let radius = 10.0;
+        app.objects.push(PhysicsObject {
+            vel: Vec2::new(0.0, -200.0),
+            ..ball(Vec2::new(500.0, radius + 5.0), radius)
+        });
+        app.update_physics(SOLVER_DT);
+        let ball = &app.objects[0];
+        assert_eq!(ball.pos.y, radius + 15.0);
+        assert!(ball.vel.y > 0.0);
+
+        // From there it just travels back down, never snapped above the line again
+        for _ in 0..30 {
+            app.update_physics(SOLVER_DT);
+            assert!(app.objects[0].pos.y >= radius + 15.0);
+        }
+    }
+
+    #[test]
+    fn only_free_intermediate_balls_hit_the_goal() {
+        let at = |x: f32| ball(Vec2::new(x, 300.0), 20.0);
+        let goal = PhysicsObject { is_goal: true, ..at(400.0) };
+        let player = PhysicsObject { is_player: true, ..at(360.0) };
+        let intermediate = at(440.0);
+        let blocker = PhysicsObject { fixed: true, ..at(440.0) };
+        let other_goal = PhysicsObject { is_goal: true, ..at(440.0) };
+
+        assert!(!is_goal_hit(&goal, &player));
+        assert!(is_goal_hit(&goal, &intermediate));
+        assert!(!is_goal_hit(&goal, &blocker));
+        assert!(!is_goal_hit(&goal, &other_goal));
+        // Only the goal side of the pair can be satisfied
+        assert!(!is_goal_hit(&intermediate, &goal));
+
+        // Two goals bumping into each other mid-attempt don't count either
+        let mut app = empty_scene();
+        app.gravity = Vec2::new(0.0, 0.0);
+        app.win_armed = true;
+        app.objects.push(goal);
+        app.objects.push(PhysicsObject { vel: Vec2::new(-100.0, 0.0), ..other_goal });
+        run(&mut app, 0.5);
+        assert!(app.satisfied_goals.is_empty());
+        assert!(!matches!(app.game_state, GameState::Won));
+    }
+
+    fn spring_on(object_index: usize) -> Spring {
+        Spring {
+            object_index,
+            anchor: None,
+            anchor_pos: Vec2::new(400.0, 100.0),
+            rest_length: 50.0,
+            stiffness: 10.0,
+        }
+    }
+
+    #[test]
+    fn undoing_a_delete_puts_springs_back_in_place() {
+        let mut app = PhysicsApp::default();
+        let a = app.objects.len();
+        app.objects.push(ball(Vec2::new(400.0, 300.0), 10.0));
+        app.objects.push(ball(Vec2::new(500.0, 300.0), 10.0));
+        let b = a + 1;
+        app.springs.push(spring_on(b));
+        app.edit(EditorCommand::AddSpring { index: app.springs.len(), spring: spring_on(a) });
+
+        let object = app.objects[b].clone();
+        app.edit(EditorCommand::RemoveObject { index: b, object });
+        assert!(app.springs.iter().all(|s| s.object_index < app.objects.len()));
+
+        app.undo();
+        app.undo();
+        // The spring added by hand is gone, the deleted ball's spring is back
+        assert_eq!(app.objects.len(), b + 1);
+        assert!(app.springs.iter().any(|s| s.object_index == b));
+        assert!(!app.springs.iter().any(|s| s.object_index == a));
+    }
+
+    #[test]
+    fn moving_platform_carries_a_ball() {
+        let mut app = empty_scene();
+        app.objects.push(PhysicsObject {
+            fixed: true,
+            kinematic: Some(KinematicPath { waypoints: vec![Vec2::new(800.0, 400.0)], speed: 60.0 }),
+            ..ball(Vec2::new(500.0, 400.0), 60.0)
+        });
+        app.objects.push(ball(Vec2::new(500.0, 325.0), 15.0));
+
+        run(&mut app, 2.0);
+        let (platform, rider) = (&app.objects[0], &app.objects[1]);
+        // Still on top, and moved along about as far as the platform
+        assert!(rider.pos.y < platform.pos.y - platform.radius);
+        assert!((rider.pos.x - platform.pos.x).abs() < 20.0, "rider at {:?}, platform at {:?}", rider.pos, platform.pos);
+    }
+
+    #[test]
+    fn headless_runs_skip_the_rewind_history() {
+        let mut app = empty_scene();
+        app.objects.push(ball(Vec2::new(500.0, 200.0), 15.0));
+        app.record_history = false;
+        run(&mut app, 0.5);
+        assert!(app.history.is_empty());
+
+        app.record_history = true;
+        run(&mut app, 0.1);
+        assert!(!app.history.is_empty());
+    }
+
+    #[test]
+    fn undoing_a_jelly_ball_removes_exactly_its_parts() {
+        let mut app = empty_scene();
+        app.objects.push(ball(Vec2::new(300.0, 300.0), 10.0));
+        app.springs.push(spring_on(0));
+        app.edit(EditorCommand::AddSoftBody { center: Vec2::new(600.0, 300.0), radius: 40.0, points: 8 });
+        assert_eq!(app.objects.len(), 10);
+        // Hooked onto the jelly from outside, it has to go with it
+        app.springs.push(Spring { anchor: Some(1), ..spring_on(0) });
+
+        app.undo();
+        assert_eq!(app.objects.len(), 1);
+        assert!(app.soft_bodies.is_empty());
+        assert_eq!(app.springs.len(), 1);
+        assert_eq!(app.springs[0].anchor, None);
+
+        app.redo();
+        assert_eq!(app.objects.len(), 10);
+        assert_eq!(app.soft_bodies.len(), 1);
+    }
+
+    #[test]
+    fn solution_search_gives_up_at_its_deadline() {
+        // Level 1 needs walls, so only the search loop could find an answer
+        let app = PhysicsApp::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(app.find_solution(&mut rng, Some(Instant::now())).is_none());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn threaded_step_matches_the_serial_one() {
+        let stepped = |parallel: bool| {
+            let mut app = PhysicsApp::benchmark_scene(200, true);
+            app.parallel_step = parallel;
+            run(&mut app, 1.0);
+            app.objects.iter().map(|o| (o.pos, o.vel)).collect::<Vec<_>>()
+        };
+        assert_eq!(stepped(false), stepped(true));
+    }
+
+    #[test]
+    fn save_round_trip_keeps_the_whole_scene() {
+        let mut app = PhysicsApp::default();
+        // Knock the layout out of step with the level's own object list
+        app.remove_object(2);
+        app.edit(EditorCommand::AddSoftBody { center: Vec2::new(300.0, 450.0), radius: 40.0, points: 6 });
+        app.objects[1].kinematic = Some(KinematicPath { waypoints: vec![Vec2::new(500.0, 300.0)], speed: 40.0 });
+        let goal = &mut app.objects[2];
+        goal.initial_pos = Vec2::new(790.0, 470.0);
+        goal.health = Some(400.0);
+        goal.damage = 120.0;
+        goal.plasticity = 0.2;
+        goal.planet = true;
+        goal.gravity_flipped = true;
+        goal.angle = 1.5;
+        app.walls[0].active = false;
+        app.switches.push(Switch {
+            pos: Vec2::new(300.0, 500.0),
+            radius: SWITCH_RADIUS,
+            target_wall_indices: vec![0],
+            touched: true,
+            flipped: true,
+        });
+
+        let json = serde_json::to_string(&app.save_state()).unwrap();
+        let mut loaded = PhysicsApp::default();
+        loaded.restore_save(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(loaded.objects.len(), app.objects.len());
+        for (a, b) in loaded.objects.iter().zip(&app.objects) {
+            assert_eq!((a.pos, a.initial_pos, a.is_goal, a.fixed), (b.pos, b.initial_pos, b.is_goal, b.fixed));
+        }
+        let goal = &loaded.objects[2];
+        assert_eq!((goal.health, goal.damage, goal.plasticity), (Some(400.0), 120.0, 0.2));
+        assert!(goal.planet && goal.gravity_flipped);
+        assert_eq!(goal.angle, 1.5);
+        assert!(loaded.objects[1].kinematic.is_some());
+        assert!(!loaded.walls[0].active);
+        assert!(loaded.switches[0].touched && loaded.switches[0].flipped);
+        assert_eq!(loaded.no_collide_pairs, vec![(0, 2)]);
+        assert_eq!(loaded.soft_bodies.len(), 1);
+        assert_eq!(loaded.soft_bodies[0].ring, app.soft_bodies[0].ring);
+    }
+}